@@ -0,0 +1,49 @@
+use std::io::{stdout, IsTerminal};
+
+/// Голубой цвет текста
+pub const CYAN: &str = "36";
+
+/// Красный цвет текста
+pub const RED: &str = "31";
+
+/// Зеленый цвет текста
+pub const GREEN: &str = "32";
+
+/// Желтый цвет текста
+pub const YELLOW: &str = "33";
+
+/// Синий цвет текста
+pub const BLUE: &str = "34";
+
+/// Пурпурный цвет текста
+pub const MAGENTA: &str = "35";
+
+/// Приглушенный текст
+pub const DIM: &str = "2";
+
+/// Оборачивание текста ANSI кодом цвета со сбросом в конце
+pub fn paint(text: &str, code: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// Код цвета по названию, например `red` или `красный`,
+/// для других слов цвета нет
+pub fn named(name: &str) -> Option<&'static str> {
+    let code = match name.to_lowercase().as_str() {
+        "red" | "красный" => RED,
+        "green" | "зеленый" | "зелёный" => GREEN,
+        "yellow" | "желтый" | "жёлтый" => YELLOW,
+        "blue" | "синий" => BLUE,
+        "magenta" | "purple" | "фиолетовый" => MAGENTA,
+        "cyan" | "голубой" => CYAN,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// Цвет включается только при выводе в терминал и если не задана
+/// непустая переменная окружения NO_COLOR
+pub fn enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && stdout().is_terminal()
+}
@@ -0,0 +1,8621 @@
+pub mod color;
+#[macro_use]
+pub mod log;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, Timelike,
+    Weekday,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::TryFrom,
+    error::Error,
+    fmt::Display,
+    fs::{
+        copy, create_dir_all, read_dir, read_to_string, remove_file, rename, write, File,
+        TryLockError,
+    },
+    io::{stdin, stdout, BufRead, BufReader, ErrorKind, IsTerminal, Stdin, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    sync::{Mutex, Once, OnceLock},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Глобальный контейнер для решения зависимостей объектов
+pub static CONTAINER: OnceLock<Container> = OnceLock::new();
+
+/// Предупреждение о пропущенных записях выводится один раз за запуск
+static SKIPPED_WARNING: Once = Once::new();
+
+/// Приложение
+pub struct App;
+
+impl App {
+    /// Запуск приложения, возвращает код завершения процесса:
+    /// 0 - успех или выход пользователя, 1 - ошибка приложения,
+    /// 2 - непредвиденная ошибка (например, ввода-вывода)
+    pub fn run(args: Vec<String>) -> ExitCode {
+        let res = || -> Result<()> {
+            let args = Args::try_from(args)?;
+            let container = CONTAINER.get_or_init(|| Container::from(&args));
+
+            if args.help {
+                print!("{}", Self::usage());
+                return Ok(());
+            }
+            if args.version {
+                println!("my-planner {}", env!("CARGO_PKG_VERSION"));
+                return Ok(());
+            }
+
+            // Путь к файлу показывается, даже если его каталога нет
+            if args.command.as_deref() != Some("where") {
+                container.storage().check_dir()?;
+            }
+            // Отмена и восстановление копии работают и с поврежденным файлом,
+            // иначе его нечем было бы исправить
+            let repair = matches!(args.command.as_deref(), Some("undo" | "restore" | "where"));
+            if !args.skip_validation && !repair {
+                container.storage().validate()?;
+            }
+
+            match args.command.as_deref() {
+                None => {
+                    HelloModel.exec()?;
+                    MenuModel {
+                        all: args.all,
+                        category: args.category.clone(),
+                    }
+                    .exec()?;
+                }
+                // Ввод команды add не из терминала, например из конвейера,
+                // читается пакетом
+                Some("add") => AddEntryModel {
+                    batch: args.batch || (!args.multi && !stdin().is_terminal()),
+                    tomorrow: args.tomorrow,
+                    multi: args.multi,
+                }
+                .exec()?,
+                Some("list") => ViewListEntryModel {
+                    all: args.all,
+                    category: args.category.clone(),
+                    json: args.json,
+                    page_size: args.page_size,
+                    range: args.range,
+                    output: args.output.clone(),
+                    compact: args.compact,
+                    reverse: args.reverse,
+                    ..Default::default()
+                }
+                .exec()?,
+                Some("tomorrow") => ViewListEntryModel {
+                    tomorrow: true,
+                    category: args.category.clone(),
+                    json: args.json,
+                    page_size: args.page_size,
+                    range: args.range,
+                    output: args.output.clone(),
+                    compact: args.compact,
+                    reverse: args.reverse,
+                    ..Default::default()
+                }
+                .exec()?,
+                Some("week") => WeekViewModel.exec()?,
+                Some("next") => NextEntryModel.exec()?,
+                Some("focus") => FocusModel.exec()?,
+                Some("gaps") => GapsModel { range: args.range }.exec()?,
+                Some("stats") => StatsModel.exec()?,
+                Some("delete") => DeleteEntryModel { id: args.id }.exec()?,
+                Some("edit") => EditEntryModel { id: args.id }.exec()?,
+                Some("move") => RescheduleModel {
+                    index: args.params.first().cloned(),
+                    time: args.params.get(1).cloned(),
+                }
+                .exec()?,
+                Some("copy") => CopyModel {
+                    index: args.params.first().cloned(),
+                    time: args.params.get(1).cloned(),
+                }
+                .exec()?,
+                Some("search") => SearchEntryModel.exec()?,
+                Some("done") => CompleteEntryModel { id: args.id }.exec()?,
+                Some("clear") => ClearModel.exec()?,
+                Some("undo") => UndoModel.exec()?,
+                Some("rollover") => RolloverModel.exec()?,
+                Some("shift") => ShiftModel {
+                    delta: args.params.first().cloned(),
+                    range: args.range,
+                }
+                .exec()?,
+                Some("archive") => ArchiveModel.exec()?,
+                Some("apply-template") => TemplateModel {
+                    name: args.params.first().cloned(),
+                    date: args.params.get(1).cloned(),
+                }
+                .exec()?,
+                Some("profiles") => ProfilesModel.exec()?,
+                Some("where") => WhereModel.exec()?,
+                #[cfg(feature = "tui")]
+                Some("tui") => tui::TuiModel.exec()?,
+                Some("restore") => RestoreModel.exec()?,
+                Some("swap") => SwapModel {
+                    first: args.params.first().cloned(),
+                    second: args.params.get(1).cloned(),
+                }
+                .exec()?,
+                Some("merge") => MergeModel {
+                    first: args.params.first().cloned(),
+                    second: args.params.get(1).cloned(),
+                }
+                .exec()?,
+                Some("recategorize") => RecategorizeModel {
+                    old: args.params.first().cloned(),
+                    new: args.params.get(1).cloned(),
+                    ignore_case: args.ignore_case,
+                }
+                .exec()?,
+                Some("import") => ImportModel {
+                    path: args.params.first().cloned(),
+                }
+                .exec()?,
+                Some("export") => ExportModel {
+                    csv: args.csv.clone(),
+                    ics: args.ics.clone(),
+                }
+                .exec()?,
+                Some(command) => Err(AppError::UnknownCommand(command.to_owned()))?,
+            }
+
+            Ok(())
+        }();
+
+        let Err(err) = res else {
+            return ExitCode::SUCCESS;
+        };
+
+        println!("{}: {}", tr("Ошибка"), err);
+
+        match err.downcast_ref::<AppError>() {
+            Some(AppError::Exit) => ExitCode::SUCCESS,
+            Some(..) => ExitCode::from(1),
+            None => ExitCode::from(2),
+        }
+    }
+
+    /// Справка по командам и флагам для --help
+    pub fn usage() -> String {
+        let commands = [
+            (
+                "(без команды)",
+                "интерактивный режим: добавление, просмотр и удаление",
+            ),
+            ("add", "добавить записи"),
+            ("list", "показать расписание на сегодня"),
+            ("tomorrow", "показать расписание на завтра"),
+            ("week", "расписание на семь дней вперед"),
+            ("next", "показать ближайшую задачу на сегодня"),
+            ("focus", "показать текущую и следующую задачу"),
+            ("gaps", "показать свободное время на сегодня"),
+            ("stats", "статистика задач по категориям и времени суток"),
+            ("delete", "удалить запись"),
+            ("edit", "изменить время или текст записи"),
+            ("move <номер> <время>", "перенести запись на другое время"),
+            ("copy <номер> <время>", "скопировать запись на другое время"),
+            (
+                "swap <номер> <номер>",
+                "поменять местами две записи на одно время",
+            ),
+            ("merge <номер> <номер>", "объединить две записи в одну"),
+            ("search", "найти записи по тексту задачи"),
+            ("done", "отметить запись выполненной или снять отметку"),
+            ("clear", "удалить все записи"),
+            ("undo", "отменить последнее изменение"),
+            ("archive", "перенести выполненные записи в архив"),
+            (
+                "apply-template <имя> [дата]",
+                "заполнить неделю записями из шаблона",
+            ),
+            ("profiles", "показать профили планеров"),
+            ("where", "показать путь к файлу планера и откуда он взят"),
+            (
+                "rollover",
+                "перенести невыполненные прошлые записи на сегодня",
+            ),
+            ("restore", "восстановить одну из резервных копий"),
+            (
+                "shift <+мин|-мин>",
+                "сдвинуть время всех записей, --from и --to ограничивают",
+            ),
+            (
+                "recategorize <старая> <новая>",
+                "переименовать категорию во всех записях",
+            ),
+            ("import <файл>", "добавить записи из другого файла планера"),
+            ("export", "выгрузить записи (--csv <файл>, --ics <файл>)"),
+        ];
+        let flags = [
+            ("--all", "показать все записи, а не только сегодняшние"),
+            ("--category <категория>", "показать одну категорию"),
+            ("--json", "вывести записи массивом JSON"),
+            ("--page <n>", "list: показывать по n записей на странице"),
+            ("--output <файл>", "list: записать список в файл"),
+            ("--compact", "list: одна строка на запись"),
+            ("--reverse", "list: от поздних записей к ранним"),
+            ("--id <n>", "delete, edit, done: запись с этим ID"),
+            (
+                "--from <время>, --to <время>",
+                "list: только записи, начинающиеся в промежутке",
+            ),
+            ("--file <файл>", "файл планера для этого запуска"),
+            ("--profile <имя>", "планер профиля, например work или home"),
+            (
+                "--sort <time|insertion|created>",
+                "порядок записей: по времени, по добавлению или от новых",
+            ),
+            ("--lang <ru|en>", "язык интерфейса"),
+            (
+                "--batch",
+                "add: читать пары строк время и задача без вопросов",
+            ),
+            (
+                "--multi",
+                "add: несколько задач на одно время, по задаче в строке",
+            ),
+            ("--tomorrow", "add: добавлять записи на завтра"),
+            ("--if-not-exists", "add: пропускать уже сохраненные записи"),
+            ("--dry-run", "показать изменения, не записывая их в файл"),
+            (
+                "--strict",
+                "ошибка вместо пропуска некорректных записей файла",
+            ),
+            (
+                "--skip-validation",
+                "не проверять файл планера перед запуском команды",
+            ),
+            ("-i, --ignore-case", "recategorize: без учета регистра"),
+            (
+                "-q, --quiet",
+                "не выводить приветствие и подтверждения изменений",
+            ),
+            ("-h, --help", "показать эту справку"),
+            ("-V, --version", "показать версию"),
+        ];
+
+        let mut usage = format!(
+            "{}: my-planner [{}] [{}]\n\n",
+            tr("Использование"),
+            tr("команда"),
+            tr("флаги")
+        );
+        usage += &format!("{}:\n", tr("Команды"));
+        for (command, description) in commands {
+            usage += &format!("  {:<32}{}\n", tr(command), tr(description));
+        }
+        usage += &format!("\n{}:\n", tr("Флаги"));
+        for (flag, description) in flags {
+            usage += &format!("  {:<32}{}\n", tr(flag), tr(description));
+        }
+
+        usage
+    }
+}
+
+/// Аргументы командной строки
+#[derive(Default, Debug)]
+pub struct Args {
+    command: Option<String>,
+    lang: Option<Lang>,
+    all: bool,
+    json: bool,
+    category: Option<String>,
+    csv: Option<String>,
+    ics: Option<String>,
+    file: Option<String>,
+    output: Option<String>,
+    params: Vec<String>,
+    help: bool,
+    version: bool,
+    batch: bool,
+    multi: bool,
+    tomorrow: bool,
+    page_size: Option<usize>,
+    dry_run: bool,
+    ignore_case: bool,
+    quiet: bool,
+    range: Option<(Time, Time)>,
+    sort_mode: Option<SortMode>,
+    strict: bool,
+    profile: Option<String>,
+    id: Option<u32>,
+    compact: bool,
+    reverse: bool,
+    if_not_exists: bool,
+    skip_validation: bool,
+}
+
+/// Разбор аргументов командной строки
+impl TryFrom<Vec<String>> for Args {
+    type Error = Box<dyn Error>;
+
+    fn try_from(args: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        let (mut from, mut to) = (None, None);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--lang" => {
+                    let name = args.next().ok_or(AppError::Msg("Не указан язык."))?;
+                    parsed.lang = Some(Lang::from_name(&name).unwrap_or_default());
+                }
+                "--all" => parsed.all = true,
+                "--json" => parsed.json = true,
+                "--batch" => parsed.batch = true,
+                "--multi" => parsed.multi = true,
+                "--tomorrow" => parsed.tomorrow = true,
+                "--dry-run" => parsed.dry_run = true,
+                "-i" | "--ignore-case" => parsed.ignore_case = true,
+                "-q" | "--quiet" => parsed.quiet = true,
+                "--strict" => parsed.strict = true,
+                "--compact" => parsed.compact = true,
+                "--reverse" => parsed.reverse = true,
+                "--if-not-exists" => parsed.if_not_exists = true,
+                "--skip-validation" => parsed.skip_validation = true,
+                "--profile" => {
+                    let name = args.next().ok_or(AppError::Msg("Не указан профиль."))?;
+                    check_profile_name(&name)?;
+                    parsed.profile = Some(name);
+                }
+                "--sort" => {
+                    let name = args.next().ok_or(AppError::Msg("Не указан порядок."))?;
+                    let sort_mode =
+                        SortMode::from_name(&name).ok_or(AppError::Msg("Неверный порядок."))?;
+                    parsed.sort_mode = Some(sort_mode);
+                }
+                "--from" => {
+                    let time = args.next().ok_or(AppError::Msg("Не указано время."))?;
+                    from = Some(time.parse::<Time>()?);
+                }
+                "--to" => {
+                    let time = args.next().ok_or(AppError::Msg("Не указано время."))?;
+                    to = Some(time.parse::<Time>()?);
+                }
+                "--page" => {
+                    let size = args
+                        .next()
+                        .ok_or(AppError::Msg("Не указан размер страницы."))?;
+                    let size = size
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|size| *size > 0)
+                        .ok_or(AppError::Msg("Неверный размер страницы."))?;
+                    parsed.page_size = Some(size);
+                }
+                "-h" | "--help" => parsed.help = true,
+                "-V" | "--version" => parsed.version = true,
+                "--category" => {
+                    let category = args.next().ok_or(AppError::Msg("Не указана категория."))?;
+                    parsed.category = Some(category);
+                }
+                "--csv" => {
+                    let path = args.next().ok_or(AppError::Msg("Не указан путь к файлу"))?;
+                    parsed.csv = Some(path);
+                }
+                "--ics" => {
+                    let path = args.next().ok_or(AppError::Msg("Не указан путь к файлу"))?;
+                    parsed.ics = Some(path);
+                }
+                "--file" => {
+                    let path = args.next().ok_or(AppError::Msg("Не указан путь к файлу"))?;
+                    parsed.file = Some(path);
+                }
+                "--id" => {
+                    let id = args.next().ok_or(AppError::Msg("Не указан ID записи."))?;
+                    let id = id
+                        .trim()
+                        .parse()
+                        .map_err(|_| AppError::Msg("Неверный ID записи."))?;
+                    parsed.id = Some(id);
+                }
+                "--output" => {
+                    let path = args.next().ok_or(AppError::Msg("Не указан путь к файлу"))?;
+                    parsed.output = Some(path);
+                }
+                _ if parsed.command.is_none() => parsed.command = Some(arg),
+                _ => parsed.params.push(arg),
+            }
+        }
+
+        // Промежуток без одной из границ продолжается до начала или конца дня
+        if from.is_some() || to.is_some() {
+            let (from, to) = (from.unwrap_or_default(), to.unwrap_or(Time::MAX));
+            if from > to {
+                Err(AppError::Msg("Начало промежутка позже его конца."))?
+            }
+            parsed.range = Some((from, to));
+        }
+
+        // Позиционные параметры после имени команды принимают не все команды
+        let max_params = match parsed.command.as_deref() {
+            Some("move" | "copy" | "swap" | "merge" | "recategorize" | "apply-template") => 2,
+            Some("import" | "shift") => 1,
+            _ => 0,
+        };
+        if let Some(param) = parsed.params.get(max_params) {
+            Err(AppError::UnknownCommand(param.clone()))?
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Ошибки приложения
+#[derive(Debug)]
+pub enum AppError {
+    Exit,
+    Msg(&'static str),
+    UnknownCommand(String),
+    Corrupted(usize),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exit => write!(f, "{}", tr("Выход")),
+            Self::Msg(s) => write!(f, "{}", tr(s)),
+            Self::UnknownCommand(command) => {
+                write!(f, "{}: {}", tr("Неизвестная команда"), command)
+            }
+            Self::Corrupted(line) => write!(
+                f,
+                "{}",
+                tr("Файл планера повреждён в строке {}").replace("{}", &line.to_string())
+            ),
+        }
+    }
+}
+
+impl Error for AppError {}
+
+/// Язык сообщений приложения
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Lang {
+    /// Получение языка по названию (`ru` или `en`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "ru" => Some(Self::Ru),
+            "en" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    /// Получение языка из переменной окружения `MY_PLANNER_LANG`
+    pub fn from_env() -> Option<Self> {
+        std::env::var("MY_PLANNER_LANG")
+            .ok()
+            .and_then(|name| Self::from_name(&name))
+    }
+
+    /// Перевод сообщения, записанного по-русски, на выбранный язык
+    pub fn translate(&self, msg: &'static str) -> &'static str {
+        if *self == Self::Ru {
+            return msg;
+        }
+
+        match msg {
+            "Ошибка" => "Error",
+            "Выход" => "Exit",
+            "Неизвестная команда" => "Unknown command",
+            "Внимание: неизвестный параметр настроек: {}" => {
+                "Warning: unknown config key: {}"
+            }
+            "Не указан язык." => "Language is not specified.",
+            "Не указан путь к файлу" => "File path is not specified",
+            "Не указан формат экспорта." => "Export format is not specified.",
+            "Экспортировано" => "Exported",
+            "Привет!" => "Hello!",
+            "Удалять нечего, расписание пусто." => {
+                "Nothing to delete, the schedule is empty."
+            }
+            "Какую запись удалить?" => "Which entry to delete?",
+            "Редактировать нечего, расписание пусто." => {
+                "Nothing to edit, the schedule is empty."
+            }
+            "Какую запись изменить?" => "Which entry to edit?",
+            "Новое время (Enter - оставить {}): " => {
+                "New time (Enter - keep {}): "
+            }
+            "Новая задача (Enter - оставить без изменений): " => {
+                "New task (Enter - keep unchanged): "
+            }
+            "Отмечать нечего, расписание пусто." => {
+                "Nothing to mark, the schedule is empty."
+            }
+            "Какую запись отметить?" => "Which entry to mark?",
+            "Записей в планере: {}" => "Entries in the planner: {}",
+            "Удалить все записи? (да/нет): " => {
+                "Delete all entries? (yes/no): "
+            }
+            "Отменено" => "Cancelled",
+            "Планер занят другим процессом" => {
+                "The planner is busy with another process"
+            }
+            "Переносить нечего, расписание пусто." => {
+                "Nothing to move, the schedule is empty."
+            }
+            "Какую запись перенести?" => "Which entry should be moved?",
+            "Новое время: " => "New time: ",
+            "Использование" => "Usage",
+            "(без команды)" => "(no command)",
+            "команда" => "command",
+            "флаги" => "flags",
+            "Команды" => "Commands",
+            "copy <номер> <время>" => "copy <number> <time>",
+            "скопировать запись на другое время" => {
+                "copy an entry to another time"
+            }
+            "Копировать нечего, расписание пусто." => {
+                "Nothing to copy, the schedule is empty."
+            }
+            "Какую запись скопировать?" => "Which entry should be copied?",
+            "Недавние задачи (номер - повторить):" => {
+                "Recent tasks (number - reuse):"
+            }
+            "Не удалось создать каталог для файла планера." => {
+                "Could not create the directory for the planner file."
+            }
+            "показать путь к файлу планера и откуда он взят" => {
+                "show the planner file path and where it comes from"
+            }
+            "Источник" => "Source",
+            "по умолчанию" => "default",
+            "переменная окружения MY_PLANNER_PATH" => {
+                "environment variable MY_PLANNER_PATH"
+            }
+            "параметр storage_path в config.toml" => "storage_path setting in config.toml",
+            "флаг --file" => "--file flag",
+            "флаг --profile" => "--profile flag",
+            "merge <номер> <номер>" => "merge <number> <number>",
+            "объединить две записи в одну" => "merge two entries into one",
+            "Нельзя объединить запись саму с собой." => {
+                "An entry cannot be merged with itself."
+            }
+            "⚠ Много задач на сегодня ({})" => "⚠ Many tasks for today ({})",
+            "⚠ Много задач на завтра ({})" => "⚠ Many tasks for tomorrow ({})",
+            "⚠ Много задач на {} ({})" => "⚠ Many tasks for {} ({})",
+            "Файл планера повреждён в строке {}" => {
+                "The planner file is corrupted at line {}"
+            }
+            "Не удалось прочитать файл планера." => {
+                "Could not read the planner file."
+            }
+            "не проверять файл планера перед запуском команды" => {
+                "do not check the planner file before running the command"
+            }
+            "Метка? (важно, red, green..., Enter - без метки): " => {
+                "Label? (important, red, green..., Enter - no label): "
+            }
+            "Слишком длинная метка" => "Label is too long",
+            "Метка не может содержать управляющие символы" => {
+                "Label cannot contain control characters"
+            }
+            "Категория не может быть пустой" => {
+                "Category cannot be empty"
+            }
+            "Категория не может содержать управляющие символы" => {
+                "Category cannot contain control characters"
+            }
+            "Внимание: не удалось записать текстовую копию: {}" => {
+                "Warning: failed to write the text mirror: {}"
+            }
+            "shift <+мин|-мин>" => "shift <+min|-min>",
+            "сдвинуть время всех записей, --from и --to ограничивают" => {
+                "shift the time of all entries, --from and --to narrow it"
+            }
+            "Не указан сдвиг." => "No shift given.",
+            "Неверный сдвиг." => "Invalid shift.",
+            "Пропущено, время вышло бы за пределы дня: {}" => {
+                "Skipped, the time would leave the day: {}"
+            }
+            "Сдвинуто записей: {}" => "Entries shifted: {}",
+            "показать свободное время на сегодня" => {
+                "show today's free time"
+            }
+            "Свободно" => "Free",
+            "Свободного времени нет" => "No free time",
+            "add: пропускать уже сохраненные записи" => {
+                "add: skip entries that are already saved"
+            }
+            "Уже существует, пропущено" => "Already exists, skipped",
+            "показать текущую и следующую задачу" => {
+                "show the current and the next task"
+            }
+            "Сейчас:" => "Now:",
+            "Далее:" => "Next:",
+            "Файл изменён извне, объединить? (да/нет): " => {
+                "The file was changed elsewhere, merge? (yes/no): "
+            }
+            "Изменение отменено: файл изменён извне." => {
+                "Change cancelled: the file was changed elsewhere."
+            }
+            "Запись изменена или удалена извне." => {
+                "The entry was changed or deleted elsewhere."
+            }
+            "Это время уже прошло. Сохранить всё равно? (да/нет): " => {
+                "This time has already passed. Save anyway? (yes/no): "
+            }
+            "list: одна строка на запись" => "list: one line per entry",
+            "list: от поздних записей к ранним" => {
+                "list: latest entries first"
+            }
+            "--id <n>" => "--id <n>",
+            "delete, edit, done: запись с этим ID" => {
+                "delete, edit, done: the entry with this ID"
+            }
+            "Не указан ID записи." => "No entry ID given.",
+            "Неверный ID записи." => "Invalid entry ID.",
+            "Записи с таким ID нет." => "There is no entry with this ID.",
+            "--output <файл>" => "--output <file>",
+            "list: записать список в файл" => "list: write the list to a file",
+            "Не удалось записать файл." => "Could not write the file.",
+            "apply-template <имя> [дата]" => "apply-template <name> [date]",
+            "заполнить неделю записями из шаблона" => {
+                "fill a week with entries from a template"
+            }
+            "Не указан шаблон." => "No template given.",
+            "Неверное имя шаблона." => "Invalid template name.",
+            "Шаблон не найден." => "Template not found.",
+            "Неверная строка шаблона." => "Invalid template line.",
+            "add: несколько задач на одно время, по задаче в строке" => {
+                "add: several tasks at one time, one task per line"
+            }
+            "Задачи на это время, по одной в строке (пустая строка - завершить):" => {
+                "Tasks at this time, one per line (empty line to finish):"
+            }
+            "Внимание: неверный формат времени: {}, используется 9:30" => {
+                "Warning: invalid time format: {}, using 9:30"
+            }
+            "--profile <имя>" => "--profile <name>",
+            "планер профиля, например work или home" => {
+                "the profile's planner, e.g. work or home"
+            }
+            "показать профили планеров" => "list planner profiles",
+            "Профилей пока нет." => "There are no profiles yet.",
+            "Не указан профиль." => "No profile given.",
+            "Неверное имя профиля." => "Invalid profile name.",
+            "На сегодня задач: {}" => "Tasks for today: {}",
+            "(ближайшая в {})" => "(next at {})",
+            "↑/↓ - выбор, Enter - действия, q - выход" => {
+                "↑/↓ - select, Enter - actions, q - quit"
+            }
+            "e - изменить, d - удалить, другая клавиша - назад" => {
+                "e - edit, d - delete, any other key - back"
+            }
+            "Этот режим работает только в терминале." => {
+                "This mode only works in a terminal."
+            }
+            "Не удалось изменить режим терминала." => {
+                "Failed to change the terminal mode."
+            }
+            "Время занято. Ввести другое время? (да/нет): " => {
+                "The time is taken. Enter another time? (yes/no): "
+            }
+            "перенести выполненные записи в архив" => {
+                "move done entries to the archive"
+            }
+            "Перенесено в архив: {}" => "Moved to the archive: {}",
+            "перенести невыполненные прошлые записи на сегодня" => {
+                "move unfinished past entries to today"
+            }
+            "Перенесено на сегодня: {}" => "Moved to today: {}",
+            "ошибка вместо пропуска некорректных записей файла" => {
+                "fail instead of skipping malformed entries in the file"
+            }
+            "Пропущено некорректных записей: {} (строки {})" => {
+                "Skipped malformed entries: {} (lines {})"
+            }
+            "Файл планера содержит некорректные записи." => {
+                "The planner file contains malformed entries."
+            }
+            "порядок записей: по времени, по добавлению или от новых" => {
+                "entry order: by time, by insertion or newest first"
+            }
+            "Не указан порядок." => "No order given.",
+            "Неверный порядок." => "Invalid order.",
+            "--from <время>, --to <время>" => "--from <time>, --to <time>",
+            "list: только записи, начинающиеся в промежутке" => {
+                "list: only entries starting within the range"
+            }
+            "Не указано время." => "No time given.",
+            "Начало промежутка позже его конца." => {
+                "The range starts after it ends."
+            }
+            "С {} до {} ничего не запланировано" => {
+                "Nothing is planned from {} to {}"
+            }
+            "не выводить приветствие и подтверждения изменений" => {
+                "do not print the greeting and change confirmations"
+            }
+            "swap <номер> <номер>" => "swap <number> <number>",
+            "поменять местами две записи на одно время" => {
+                "swap two entries at the same time"
+            }
+            "Поменять местами можно только записи на одно время." => {
+                "Only entries at the same time can be swapped."
+            }
+            "Записи с разным приоритетом упорядочены по приоритету." => {
+                "Entries with different priorities are ordered by priority."
+            }
+            "нет задач" => "no tasks",
+            "восстановить одну из резервных копий" => {
+                "restore one of the backups"
+            }
+            "Резервных копий нет." => "There are no backups.",
+            "Какую копию восстановить? (номер, Enter - пропустить): " => {
+                "Which backup to restore? (number, Enter - skip): "
+            }
+            "Копии с таким номером нет." => {
+                "There is no backup with this number."
+            }
+            "Копия восстановлена" => "Backup restored",
+            "переименовать категорию во всех записях" => {
+                "rename a category in all entries"
+            }
+            "recategorize: без учета регистра" => "recategorize: ignore case",
+            "Изменено записей: {}" => "Entries changed: {}",
+            "весь день" => "all day",
+            "Без времени" => "No time",
+            "Добавить" => "Add",
+            "Список" => "List",
+            "Удалить" => "Delete",
+            "Что сделать? (номер): " => "What to do? (number): ",
+            "Неверный пункт меню." => "Invalid menu item.",
+            "Флаги" => "Flags",
+            "интерактивный режим: добавление, просмотр и удаление" => {
+                "interactive mode: add, view and delete"
+            }
+            "добавить записи" => "add entries",
+            "показать расписание на сегодня" => "show today's schedule",
+            "показать ближайшую задачу на сегодня" => {
+                "show the next task for today"
+            }
+            "На сегодня всё" => "That's all for today",
+            "статистика задач по категориям и времени суток" => {
+                "task statistics by category and time of day"
+            }
+            "По категориям" => "By category",
+            "По времени суток" => "By time of day",
+            "Утро" => "Morning",
+            "День" => "Afternoon",
+            "Вечер" => "Evening",
+            "Ночь" => "Night",
+            "Всего задач" => "Total tasks",
+            "расписание на семь дней вперед" => {
+                "schedule for the next seven days"
+            }
+            "удалить запись" => "delete an entry",
+            "изменить время или текст записи" => {
+                "change an entry's time or text"
+            }
+            "move <номер> <время>" => "move <number> <time>",
+            "recategorize <старая> <новая>" => "recategorize <old> <new>",
+            "перенести запись на другое время" => {
+                "move an entry to another time"
+            }
+            "найти записи по тексту задачи" => "find entries by task text",
+            "отметить запись выполненной или снять отметку" => {
+                "mark an entry done or not done"
+            }
+            "удалить все записи" => "delete all entries",
+            "отменить последнее изменение" => "undo the last change",
+            "import <файл>" => "import <file>",
+            "добавить записи из другого файла планера" => {
+                "add entries from another planner file"
+            }
+            "выгрузить записи (--csv <файл>, --ics <файл>)" => {
+                "export entries (--csv <file>, --ics <file>)"
+            }
+            "показать все записи, а не только сегодняшние" => {
+                "show all entries, not only today's"
+            }
+            "--category <категория>" => "--category <category>",
+            "показать одну категорию" => "show a single category",
+            "вывести записи массивом JSON" => "print entries as a JSON array",
+            "--file <файл>" => "--file <file>",
+            "файл планера для этого запуска" => {
+                "planner file for this run"
+            }
+            "язык интерфейса" => "interface language",
+            "показать эту справку" => "show this help",
+            "показать изменения, не записывая их в файл" => {
+                "show changes without writing them to the file"
+            }
+            "(пробный запуск, изменения не сохранены)" => {
+                "(dry run, changes were not saved)"
+            }
+            "показать расписание на завтра" => "show tomorrow's schedule",
+            "add: добавлять записи на завтра" => {
+                "add: add entries for tomorrow"
+            }
+            "На завтра ничего не запланировано" => {
+                "Nothing is planned for tomorrow"
+            }
+            "На {} ничего не запланировано" => "Nothing is planned for {}",
+            "list: показывать по n записей на странице" => {
+                "list: show n entries per page"
+            }
+            "Не указан размер страницы." => "The page size is missing.",
+            "Неверный размер страницы." => "Invalid page size.",
+            "Страница {}/{}" => "Page {}/{}",
+            "Ещё? (Enter)" => "More? (Enter)",
+            "add: читать пары строк время и задача без вопросов" => {
+                "add: read time and task line pairs without prompts"
+            }
+            "Добавлено записей: {}" => "Entries added: {}",
+            "строка {}: " => "line {}: ",
+            "нет задачи после времени" => "no task after the time",
+            "показать версию" => "show the version",
+            "Импортировано {} записей" => "Imported {} entries",
+            "Файл для импорта не найден." => {
+                "The file to import was not found."
+            }
+            "Неверное время: укажите часы и минуты, например 9:30." => {
+                "Invalid time: enter hours and minutes, for example 9:30."
+            }
+            "Достигнут лимит записей" => "The entry limit has been reached",
+            "Нечего отменять." => "Nothing to undo.",
+            "Последнее изменение отменено" => {
+                "The last change was undone"
+            }
+            "Что ищем?: " => "Search for: ",
+            "Ничего не найдено" => "Nothing found",
+            "Найдено:" => "Found:",
+            "{} (номер, Enter - пропустить): " => "{} (number, Enter - skip): ",
+            "{} (номер, время или часть задачи, Enter - пропустить): " => {
+                "{} (number, time or part of the task, Enter - skip): "
+            }
+            "Подходят несколько записей:" => "Several entries match:",
+            "Запись не найдена." => "Entry not found.",
+            "Подходят несколько записей, укажите номер." => {
+                "Several entries match, specify the number."
+            }
+            "Неверный номер записи." => "Invalid entry number.",
+            "Что планируешь делать? (выход - завершить): " => {
+                "What are you planning to do? (exit - finish): "
+            }
+            "Задача не может быть пустой" => "The task cannot be empty",
+            "Слишком длинная задача" => "The task is too long",
+            "Задача не может содержать управляющие символы" => {
+                "The task cannot contain control characters"
+            }
+            "На какой день? (пример 2024-06-01, Enter - сегодня): " => {
+                "Which day? (example 2024-06-01, Enter - today): "
+            }
+            "Неверная дата." => "Invalid date.",
+            "Во сколько (пример 9:30, +30, сейчас, Enter - весь день): " => {
+                "At what time (example 9:30, +30, now, Enter - all day): "
+            }
+            "Приоритет (1-5, Enter - {})?: " => "Priority (1-5, Enter - {})?: ",
+            "Неверный приоритет." => "Invalid priority.",
+            "Сколько минут? (опционально): " => {
+                "How many minutes? (optional): "
+            }
+            "Неверная длительность." => "Invalid duration.",
+            "Неверное время напоминания." => "Invalid reminder time.",
+            "За сколько минут напомнить? (опционально): " => {
+                "Remind how many minutes before? (optional): "
+            }
+            "Напомнить: за {} мин" => "Remind: {} min before",
+            "⏰ скоро" => "⏰ soon",
+            "прошло" => "past",
+            "Категория? (работа/дом/..., Enter - {}): " => {
+                "Category? (work/home/..., Enter - {}): "
+            }
+            "Не указана категория." => "Category is not specified.",
+            "Повторять? (ежедневно/еженедельно, Enter - нет): " => {
+                "Repeat? (daily/weekly, Enter - no): "
+            }
+            "Неверный повтор." => "Invalid recurrence.",
+            "Заметки (Enter дважды для завершения):" => {
+                "Notes (press Enter twice to finish):"
+            }
+            "Заметки" => "Notes",
+            "Повтор" => "Repeats",
+            "ежедневно" => "daily",
+            "еженедельно" => "weekly",
+            "пн" => "Mon",
+            "вт" => "Tue",
+            "ср" => "Wed",
+            "чт" => "Thu",
+            "пт" => "Fri",
+            "сб" => "Sat",
+            "вс" => "Sun",
+            "Неверное время." => "Invalid time.",
+            "Неверный формат файла." => "Invalid file format.",
+            "Дата" => "Date",
+            "Время" => "Time",
+            "Задача" => "Task",
+            "Приоритет" => "Priority",
+            "Мое расписание:" => "My schedule:",
+            "— свободно —" => "— free —",
+            "Всего задач: {} (выполнено: {})" => "Total tasks: {} (done: {})",
+            "Расписание пусто. Добавь первую задачу!" => {
+                "The schedule is empty. Add your first task!"
+            }
+            "На сегодня ничего не запланировано" => {
+                "Nothing planned for today"
+            }
+            "Внимание: на это время уже есть задача" => {
+                "Warning: there is already a task at this time"
+            }
+            "Сохранено" => "Saved",
+            "Удалено" => "Deleted",
+            "Записи с таким номером нет." => {
+                "There is no entry with this number."
+            }
+            "Каталог для файла планера не существует." => {
+                "The directory for the planner file does not exist."
+            }
+            msg => msg,
+        }
+    }
+}
+
+/// Перевод сообщения на язык, выбранный в контейнере
+pub fn tr(msg: &'static str) -> &'static str {
+    CONTAINER
+        .get()
+        .map_or_else(|| Lang::from_env().unwrap_or_default(), |c| c.lang())
+        .translate(msg)
+}
+
+/// Интерфейс для моделей
+pub trait ModelTrait: Default {
+    fn exec(&self) -> Result<()>;
+}
+
+/// Модели
+
+/// Модель приветствия
+#[derive(Default)]
+pub struct HelloModel;
+
+impl ModelTrait for HelloModel {
+    fn exec(&self) -> Result<()> {
+        if quiet() {
+            return Ok(());
+        }
+
+        // Нечитаемый планер не мешает поздороваться
+        let list = CONTAINER
+            .get()
+            .unwrap()
+            .storage()
+            .read()
+            .unwrap_or_default();
+        println!("{}", Self::greeting(list, Local::now().naive_local()));
+
+        Ok(())
+    }
+}
+
+impl HelloModel {
+    /// Приветствие с числом задач на сегодня и временем ближайшей,
+    /// без задач на сегодня - просто "Привет!"
+    pub fn greeting(list: Vec<Box<dyn EntryTrait>>, now: NaiveDateTime) -> String {
+        let count = expand_range(
+            list.iter()
+                .map(|entry| Entry::from(entry.as_ref()).into())
+                .collect(),
+            logical_date(now, day_start_hour()),
+            logical_date(now, day_start_hour()),
+        )
+        .len();
+        if count == 0 {
+            return tr("Привет!").to_owned();
+        }
+
+        let mut greeting = format!(
+            "{} {}",
+            tr("Привет!"),
+            tr("На сегодня задач: {}").replace("{}", &count.to_string())
+        );
+        if let Some(time) = NextEntryModel::find(list, now).and_then(|entry| entry.time()) {
+            greeting += &format!(
+                " {}",
+                tr("(ближайшая в {})").replace("{}", &format_time(time))
+            );
+        }
+
+        greeting
+    }
+}
+
+/// Модель главного меню интерактивного режима, повторяет выбор
+/// действия до выхода
+#[derive(Default)]
+pub struct MenuModel {
+    pub all: bool,
+    pub category: Option<String>,
+}
+
+impl ModelTrait for MenuModel {
+    fn exec(&self) -> Result<()> {
+        loop {
+            println!("1. {}", tr("Добавить"));
+            println!("2. {}", tr("Список"));
+            println!("3. {}", tr("Удалить"));
+            println!("4. {}", tr("Выход"));
+            print!("{}", tr("Что сделать? (номер): "));
+            stdout().flush()?;
+
+            let res = match read_line_or_exit(&stdin()) {
+                Ok(choice) => match choice.as_str() {
+                    "1" => AddEntryModel::default().exec(),
+                    "2" => ViewListEntryModel {
+                        all: self.all,
+                        category: self.category.clone(),
+                        ..Default::default()
+                    }
+                    .exec(),
+                    "3" => DeleteEntryModel::default().exec(),
+                    "4" => Err(AppError::Exit.into()),
+                    choice if EXIT_WORDS.contains(&choice.to_lowercase().as_str()) => {
+                        Err(AppError::Exit.into())
+                    }
+                    _ => Err(AppError::Msg("Неверный пункт меню.").into()),
+                },
+                Err(e) => Err(e),
+            };
+
+            // Ошибка одного действия не завершает работу с меню
+            match res {
+                Err(e) if matches!(e.downcast_ref(), Some(&AppError::Exit)) => return Ok(()),
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+                Ok(()) => {}
+            }
+        }
+    }
+}
+
+/// Модель добавления новой записи в планер, в пакетном режиме
+/// записи читаются из стандартного ввода без вопросов
+#[derive(Default)]
+pub struct AddEntryModel {
+    pub batch: bool,
+    pub tomorrow: bool,
+    pub multi: bool,
+}
+
+impl ModelTrait for AddEntryModel {
+    fn exec(&self) -> Result<()> {
+        // С флагом --tomorrow дата записей заполняется завтрашней
+        let date = self.tomorrow.then(tomorrow);
+
+        if self.batch {
+            let date = date.unwrap_or_else(today).format(DATE_FORMAT).to_string();
+            let (list, errors) = Self::parse_batch(stdin().lock(), &date)?;
+            for error in errors {
+                eprintln!("{}: {}", tr("Ошибка"), error);
+            }
+
+            let count = CONTAINER
+                .get()
+                .unwrap()
+                .storage()
+                .save_all(list.into_iter().map(Into::into).collect())?;
+            if !quiet() {
+                println!(
+                    "{}",
+                    tr("Добавлено записей: {}").replace("{}", &count.to_string())
+                );
+            }
+
+            return Ok(());
+        }
+
+        if self.multi {
+            let date = date.unwrap_or_else(today).format(DATE_FORMAT).to_string();
+            return match Self::read_multi(&stdin(), &date) {
+                Err(e) if matches!(e.downcast_ref(), Some(&AppError::Exit)) => Ok(()),
+                res => res,
+            };
+        }
+
+        match || -> Result<()> {
+            let storage = CONTAINER.get().unwrap().storage();
+            loop {
+                let mut entry = Entry::read_from(&stdin(), date)?;
+
+                // На занятое или уже прошедшее время можно ввести другое
+                // или сохранить запись как есть
+                loop {
+                    let retry = if storage.has_conflict(&entry)? {
+                        let answer = input(tr("Время занято. Ввести другое время? (да/нет): "))?;
+                        matches!(answer.to_lowercase().as_str(), "да" | "yes")
+                    } else if entry.is_past(Local::now().naive_local()) {
+                        let answer =
+                            input(tr("Это время уже прошло. Сохранить всё равно? (да/нет): "))?;
+                        !matches!(answer.to_lowercase().as_str(), "да" | "yes")
+                    } else {
+                        false
+                    };
+                    if !retry {
+                        break;
+                    }
+                    entry.time = Entry::read_time(&stdin())?;
+                }
+
+                entry.save()?;
+            }
+        }() {
+            Err(e) if matches!(e.downcast_ref(), Some(&AppError::Exit)) => Ok(()),
+            res => res,
+        }
+    }
+}
+
+impl AddEntryModel {
+    /// Несколько задач на одно время: время вводится один раз, затем задачи
+    /// по одной в строке до пустой строки или конца ввода
+    fn read_multi(stdin: &Stdin, date: &str) -> Result<()> {
+        let time = Entry::read_time(stdin)?;
+        println!(
+            "{}",
+            tr("Задачи на это время, по одной в строке (пустая строка - завершить):")
+        );
+
+        let mut list = Vec::new();
+        loop {
+            let target = match read_line_or_exit(stdin) {
+                Err(e) if matches!(e.downcast_ref(), Some(&AppError::Exit)) => break,
+                target => Entry::normalize_target(&target?),
+            };
+            if target.is_empty() {
+                break;
+            }
+            if let Err(e) = Entry::check_target(&target, max_target_length()) {
+                eprintln!("{}: {}", tr("Ошибка"), e);
+                continue;
+            }
+
+            list.push(Entry {
+                date: date.to_owned(),
+                time,
+                target,
+                offset: Some(local_offset()),
+                created_at: timestamp(),
+                ..Default::default()
+            });
+        }
+
+        let count = match list.is_empty() {
+            true => 0,
+            false => CONTAINER
+                .get()
+                .unwrap()
+                .storage()
+                .save_all(list.into_iter().map(Into::into).collect())?,
+        };
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Добавлено записей: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Разбор пакетного ввода: пары строк "время" и "задача", пустые строки
+    /// пропускаются. Пара с ошибкой пропускается, а ошибка возвращается
+    /// вместе с номером строки
+    pub fn parse_batch(reader: impl BufRead, date: &str) -> Result<(Vec<Entry>, Vec<String>)> {
+        let mut list = Vec::new();
+        let mut errors = Vec::new();
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(i, line)| line.map(|line| (i + 1, line.trim().to_owned())))
+            .filter(|line| !matches!(line, Ok((_, line)) if line.is_empty()));
+
+        while let Some((number, time)) = lines.next().transpose()? {
+            let line_error =
+                |msg: &'static str| tr("строка {}: ").replace("{}", &number.to_string()) + tr(msg);
+
+            let Some((_, target)) = lines.next().transpose()? else {
+                errors.push(line_error("нет задачи после времени"));
+                break;
+            };
+            let target = Entry::normalize_target(&target);
+            if let Err(e) = Entry::check_target(&target, max_target_length()) {
+                errors.push(tr("строка {}: ").replace("{}", &number.to_string()) + &e.to_string());
+                continue;
+            }
+
+            if ALL_DAY_WORDS.contains(&time.to_lowercase().as_str()) {
+                list.push(Entry {
+                    date: date.to_owned(),
+                    target,
+                    offset: Some(local_offset()),
+                    created_at: timestamp(),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            match time.parse::<Time>() {
+                Ok(time) => list.push(Entry {
+                    offset: Some(local_offset()),
+                    created_at: timestamp(),
+                    ..Entry::new(date, time, &target)
+                }),
+                Err(AppError::Msg(msg)) => errors.push(line_error(msg)),
+                Err(_) => errors.push(line_error("Неверное время.")),
+            }
+        }
+
+        Ok((list, errors))
+    }
+}
+
+/// Модель отображения записей в планере,
+/// по умолчанию показывает только записи на сегодня
+#[derive(Default)]
+pub struct ViewListEntryModel {
+    pub all: bool,
+    pub tomorrow: bool,
+    pub category: Option<String>,
+    pub json: bool,
+    pub page_size: Option<usize>,
+    pub range: Option<(Time, Time)>,
+    pub output: Option<String>,
+    pub compact: bool,
+    pub reverse: bool,
+}
+
+impl ModelTrait for ViewListEntryModel {
+    fn exec(&self) -> Result<()> {
+        let view = if self.all {
+            ListView::default()
+        } else if self.tomorrow {
+            ListView::tomorrow()
+        } else {
+            ListView::today()
+        };
+        let view = view
+            .with_category(self.category.clone())
+            .with_now(Some(Local::now().naive_local()))
+            .with_color(color::enabled() && self.output.is_none())
+            .with_compact(self.compact)
+            .with_reverse(self.reverse);
+        let storage = CONTAINER.get().unwrap().storage();
+
+        // С --output список записывается в файл в том же виде, что и в терминал
+        let mut out: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(
+                File::create(path).map_err(|_| AppError::Msg("Не удалось записать файл."))?,
+            ),
+            None => Box::new(stdout()),
+        };
+        let list = match self.range {
+            Some((from, to)) => storage.in_range(from, to)?,
+            None => storage.read()?,
+        };
+
+        // Пустой промежуток показывается отдельным сообщением, а не
+        // пустым расписанием
+        if let Some((from, to)) = self.range.filter(|_| !self.json) {
+            let copy = list
+                .iter()
+                .map(|entry| Entry::from(entry.as_ref()).into())
+                .collect();
+            if view.select(copy).is_empty() {
+                let message = tr("С {} до {} ничего не запланировано")
+                    .replacen("{}", &from.to_string(), 1)
+                    .replacen("{}", &to.to_string(), 1);
+                return Self::write(&mut out, &message);
+            }
+        }
+
+        // Постраничный вывод нужен только в терминале, в файл или другую
+        // программу список выводится целиком
+        let page_size = self
+            .page_size
+            .filter(|_| stdin().is_terminal() && stdout().is_terminal() && self.output.is_none());
+
+        if self.json {
+            // Порядок JSON совпадает с порядком списка, даже если файл
+            // изменен вручную и не отсортирован
+            let mut list = view.select(list);
+            sort_entries(&mut list);
+            if self.reverse {
+                list.reverse();
+            }
+            Self::write(&mut out, &JsonView.render(&list))?;
+        } else if let Some(page_size) = page_size {
+            let pages = view.render_pages(list, page_size);
+            let count = pages.len();
+            for (i, page) in pages.into_iter().enumerate() {
+                println!("{}", page);
+                if i + 1 < count && !input(tr("Ещё? (Enter)"))?.is_empty() {
+                    break;
+                }
+            }
+        } else {
+            Self::write(&mut out, &view.render(list))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ViewListEntryModel {
+    /// Вывод текста в терминал или в файл из --output
+    fn write(out: &mut dyn Write, text: &str) -> Result<()> {
+        writeln!(out, "{}", text)
+            .and_then(|_| out.flush())
+            .map_err(|_| AppError::Msg("Не удалось записать файл."))?;
+
+        Ok(())
+    }
+}
+
+/// Модель отображения ближайшей предстоящей записи на сегодня
+#[derive(Default)]
+pub struct NextEntryModel;
+
+impl ModelTrait for NextEntryModel {
+    fn exec(&self) -> Result<()> {
+        let list = CONTAINER.get().unwrap().storage().read()?;
+
+        match Self::find(list, Local::now().naive_local()) {
+            Some(entry) => println!("{}", entry),
+            None => println!("{}", tr("На сегодня всё")),
+        }
+
+        Ok(())
+    }
+}
+
+impl NextEntryModel {
+    /// Первая невыполненная запись дня, которая начинается не раньше
+    /// переданного момента
+    pub fn find(list: Vec<Box<dyn EntryTrait>>, now: NaiveDateTime) -> Option<Box<dyn EntryTrait>> {
+        let day = logical_date(now, day_start_hour());
+        let now = now
+            .with_second(0)
+            .unwrap_or(now)
+            .with_nanosecond(0)
+            .unwrap_or(now);
+
+        expand_range(list, day, day)
+            .into_iter()
+            .filter(|entry| !entry.done())
+            .filter_map(|entry| Some((starts_at(day, entry.time()?), entry)))
+            .filter(|(start, _)| *start >= now)
+            .min_by_key(|(start, _)| *start)
+            .map(|(_, entry)| entry)
+    }
+}
+
+/// Модель режима фокуса: только текущая и следующая задача на сегодня
+#[derive(Default)]
+pub struct FocusModel;
+
+impl ModelTrait for FocusModel {
+    fn exec(&self) -> Result<()> {
+        let list = CONTAINER.get().unwrap().storage().read()?;
+        let focus = Self::focus(list, Local::now().naive_local());
+
+        if focus.current.is_none() && focus.next.is_none() {
+            println!("{}", tr("На сегодня всё"));
+        }
+        if let Some(current) = focus.current {
+            println!("{}\n{}", tr("Сейчас:"), current);
+        }
+        if let Some(next) = focus.next {
+            println!("{}\n{}", tr("Далее:"), next);
+        }
+
+        Ok(())
+    }
+}
+
+/// Текущая и следующая задачи режима фокуса
+#[derive(Default)]
+pub struct Focus {
+    pub current: Option<Box<dyn EntryTrait>>,
+    pub next: Option<Box<dyn EntryTrait>>,
+}
+
+impl FocusModel {
+    /// Текущая и следующая невыполненные записи дня. Текущая - последняя
+    /// начавшаяся запись, которая еще не закончилась по длительности,
+    /// запись без длительности остается текущей до начала следующей
+    pub fn focus(list: Vec<Box<dyn EntryTrait>>, now: NaiveDateTime) -> Focus {
+        let day = logical_date(now, day_start_hour());
+        let mut started = expand_range(list, day, day)
+            .into_iter()
+            .filter(|entry| !entry.done())
+            .filter_map(|entry| Some((starts_at(day, entry.time()?), entry)))
+            .collect::<Vec<_>>();
+        started.sort_by_key(|(start, _)| *start);
+
+        let upcoming = started.split_off(started.partition_point(|(start, _)| *start <= now));
+        let current = started.pop().filter(|(start, entry)| {
+            entry
+                .duration_minutes()
+                .is_none_or(|duration| *start + chrono::Duration::minutes(duration.into()) > now)
+        });
+
+        Focus {
+            current: current.map(|(_, entry)| entry),
+            next: upcoming.into_iter().next().map(|(_, entry)| entry),
+        }
+    }
+}
+
+/// Модель свободного времени на сегодня в пределах рабочего дня,
+/// по умолчанию с 8:00 до 22:00, --from и --to задают другие границы
+#[derive(Default)]
+pub struct GapsModel {
+    pub range: Option<(Time, Time)>,
+}
+
+impl ModelTrait for GapsModel {
+    fn exec(&self) -> Result<()> {
+        let container = CONTAINER.get().unwrap();
+        let (from, to) = self.range.unwrap_or(container.work_hours());
+        let list = expand_range(container.storage().read()?, today(), today());
+
+        // Задача без длительности занимает только момент начала
+        let busy = list
+            .iter()
+            .filter_map(|entry| Some((entry.time()?, entry.end_time()?)))
+            .collect::<Vec<_>>();
+        let gaps = Self::gaps(&busy, from, to);
+
+        if gaps.is_empty() {
+            println!("{}", tr("Свободного времени нет"));
+        }
+        for (start, end) in gaps {
+            println!(
+                "{}: {}–{}",
+                tr("Свободно"),
+                format_time(start),
+                format_time(end)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl GapsModel {
+    /// Свободные промежутки между занятыми промежутками `busy`
+    /// в пределах окна `from`–`to`
+    pub fn gaps(busy: &[(Time, Time)], from: Time, to: Time) -> Vec<(Time, Time)> {
+        let mut busy = busy.to_vec();
+        busy.sort();
+
+        let mut gaps = Vec::new();
+        let mut cursor = from;
+        for (start, end) in busy {
+            if start.min(to) > cursor {
+                gaps.push((cursor, start.min(to)));
+            }
+            cursor = cursor.max(end);
+        }
+        if cursor < to {
+            gaps.push((cursor, to));
+        }
+
+        gaps
+    }
+}
+
+/// Модель статистики задач по категориям и времени суток
+#[derive(Default)]
+pub struct StatsModel;
+
+impl ModelTrait for StatsModel {
+    fn exec(&self) -> Result<()> {
+        let list = CONTAINER.get().unwrap().storage().read()?;
+        print!("{}", Self::render(&list));
+
+        Ok(())
+    }
+}
+
+impl StatsModel {
+    /// Часть суток по часу начала: ночь до 6:00, утро до 12:00,
+    /// день до 18:00, вечер до полуночи
+    pub fn time_of_day(time: Time) -> &'static str {
+        match time.hours() {
+            0..=5 => "Ночь",
+            6..=11 => "Утро",
+            12..=17 => "День",
+            _ => "Вечер",
+        }
+    }
+
+    /// Таблица с числом задач по категориям, по частям суток и всего
+    pub fn render(list: &[Box<dyn EntryTrait>]) -> String {
+        let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut parts = [
+            ("Утро", 0),
+            ("День", 0),
+            ("Вечер", 0),
+            ("Ночь", 0),
+            ("Без времени", 0),
+        ];
+        for entry in list {
+            *categories.entry(entry.category()).or_default() += 1;
+            if let Some(part) = parts
+                .iter_mut()
+                .find(|(name, _)| *name == entry.time().map_or("Без времени", Self::time_of_day))
+            {
+                part.1 += 1;
+            }
+        }
+
+        let mut output = String::new();
+        output += "====================================\n";
+        output += &format!("{}:\n", tr("По категориям"));
+        for (category, count) in categories {
+            output += &format!("  {:<24}{:>6}\n", category, count);
+        }
+        output += &format!("\n{}:\n", tr("По времени суток"));
+        for (part, count) in parts {
+            output += &format!("  {:<24}{:>6}\n", tr(part), count);
+        }
+        output += &format!("\n  {:<24}{:>6}\n", tr("Всего задач"), list.len());
+        output += "====================================\n";
+
+        output
+    }
+}
+
+/// Модель отображения записей на ближайшие семь дней по дням
+#[derive(Default)]
+pub struct WeekViewModel;
+
+impl ModelTrait for WeekViewModel {
+    fn exec(&self) -> Result<()> {
+        let list = CONTAINER.get().unwrap().storage().read()?;
+
+        println!("====================================");
+        for (date, list) in Self::buckets(list, today()) {
+            println!(
+                "{}, {}",
+                date.format(DATE_FORMAT),
+                weekday_name(date.weekday())
+            );
+            if list.is_empty() {
+                println!("{}\n", tr("— свободно —"));
+            } else {
+                println!("{}", ListView::format_entries(&list));
+            }
+        }
+        println!("====================================");
+
+        Ok(())
+    }
+}
+
+impl WeekViewModel {
+    /// Распределение записей по семи дням, начиная с `start`,
+    /// записи без даты в недельный вид не попадают
+    pub fn buckets(
+        list: Vec<Box<dyn EntryTrait>>,
+        start: NaiveDate,
+    ) -> Vec<(NaiveDate, Vec<Box<dyn EntryTrait>>)> {
+        let end = start + chrono::Days::new(6);
+        let mut buckets = start
+            .iter_days()
+            .take(7)
+            .map(|date| (date, Vec::new()))
+            .collect::<Vec<(NaiveDate, Vec<Box<dyn EntryTrait>>)>>();
+
+        // Записи уже отсортированы, поэтому внутри дня идут по времени
+        for entry in expand_range(list, start, end) {
+            let Ok(date) = NaiveDate::parse_from_str(entry.date(), DATE_FORMAT) else {
+                continue;
+            };
+            if let Some((_, bucket)) = buckets.iter_mut().find(|(day, _)| *day == date) {
+                bucket.push(entry);
+            }
+        }
+
+        buckets
+    }
+}
+
+/// Модель удаления записи из планера, запись можно указать ID
+#[derive(Default)]
+pub struct DeleteEntryModel {
+    pub id: Option<u32>,
+}
+
+impl ModelTrait for DeleteEntryModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        if let Some(id) = self.id {
+            return storage.delete(storage.index_of(id)?);
+        }
+        let list = storage.read()?;
+
+        if list.is_empty() {
+            println!("{}", tr("Удалять нечего, расписание пусто."));
+            return Ok(());
+        }
+
+        match select_entry(&list, tr("Какую запись удалить?"))? {
+            Some(index) => storage.delete(index),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Модель переноса записи на другое время, номер записи и время
+/// можно передать параметрами команды, иначе они запрашиваются
+#[derive(Default)]
+pub struct RescheduleModel {
+    pub index: Option<String>,
+    pub time: Option<String>,
+}
+
+impl ModelTrait for RescheduleModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let list = storage.read()?;
+
+        if list.is_empty() && self.index.is_none() {
+            println!("{}", tr("Переносить нечего, расписание пусто."));
+            return Ok(());
+        }
+        let Some(index) = find_entry(&list, self.index.as_deref(), tr("Какую запись перенести?"))?
+        else {
+            return Ok(());
+        };
+        let Some(entry) = list.get(index) else {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        };
+
+        let time = read_new_time(self.time.as_deref())?;
+        let mut entry = Entry::from(entry.as_ref());
+        entry.time = Some(time);
+        entry.offset = Some(local_offset());
+        storage.update(index, entry)
+    }
+}
+
+/// Запись по номеру или части текста задачи из аргумента команды, без
+/// аргумента запись выбирается из списка, и пустой ответ отменяет выбор
+fn find_entry(
+    list: &[Box<dyn EntryTrait>],
+    arg: Option<&str>,
+    question: &str,
+) -> Result<Option<usize>> {
+    let index = match arg {
+        Some(number) if number.chars().all(|c| c.is_ascii_digit()) => parse_index(number)?,
+        Some(query) => match Storage::matches(list, query).as_slice() {
+            [] => Err(AppError::Msg("Запись не найдена."))?,
+            [index] => *index,
+            _ => Err(AppError::Msg("Подходят несколько записей, укажите номер."))?,
+        },
+        None => return select_entry(list, question),
+    };
+
+    Ok(Some(index))
+}
+
+/// Новое время из аргумента команды или из ответа пользователя
+fn read_new_time(arg: Option<&str>) -> Result<Time> {
+    let time = match arg {
+        Some(time) => time.to_owned(),
+        None => input(tr("Новое время: "))?,
+    };
+    let time = Entry::resolve_time(&time, Local::now().time())
+        .map_err(|_| AppError::Msg("Неверное время."))?;
+
+    Ok(time)
+}
+
+/// Модель копирования записи на другое время того же дня: копия получает
+/// задачу, категорию, приоритет, длительность и метку, а исходная запись
+/// не меняется
+#[derive(Default)]
+pub struct CopyModel {
+    pub index: Option<String>,
+    pub time: Option<String>,
+}
+
+impl ModelTrait for CopyModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let list = storage.read()?;
+
+        if list.is_empty() && self.index.is_none() {
+            println!("{}", tr("Копировать нечего, расписание пусто."));
+            return Ok(());
+        }
+        let Some(index) = find_entry(
+            &list,
+            self.index.as_deref(),
+            tr("Какую запись скопировать?"),
+        )?
+        else {
+            return Ok(());
+        };
+        let Some(original) = list.get(index) else {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        };
+
+        let copy = Entry {
+            date: original.date().to_owned(),
+            time: Some(read_new_time(self.time.as_deref())?),
+            target: original.target().to_owned(),
+            priority: original.priority(),
+            duration_minutes: original.duration_minutes(),
+            category: original.category().to_owned(),
+            label: original.label().map(str::to_owned),
+            offset: Some(local_offset()),
+            created_at: timestamp(),
+            ..Default::default()
+        };
+        storage.save(copy.into())
+    }
+}
+
+/// Модель редактирования записи в планере, запись можно указать ID
+#[derive(Default)]
+pub struct EditEntryModel {
+    pub id: Option<u32>,
+}
+
+impl ModelTrait for EditEntryModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        if let Some(id) = self.id {
+            return Self::edit(storage.index_of(id)?);
+        }
+        let list = storage.read()?;
+
+        if list.is_empty() {
+            println!("{}", tr("Редактировать нечего, расписание пусто."));
+            return Ok(());
+        }
+
+        let Some(index) = select_entry(&list, tr("Какую запись изменить?"))?
+        else {
+            return Ok(());
+        };
+
+        Self::edit(index)
+    }
+}
+
+impl EditEntryModel {
+    /// Изменение записи с известным индексом: вопросы о новом времени
+    /// и тексте задачи, пустой ответ оставляет значение без изменений
+    pub fn edit(index: usize) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let list = storage.read()?;
+        let Some(entry) = list.get(index) else {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        };
+        let mut entry = Entry::from(entry.as_ref());
+
+        loop {
+            let current = entry
+                .time
+                .map_or_else(|| tr("весь день").to_owned(), |time| time.to_string());
+            let time = input(&tr("Новое время (Enter - оставить {}): ").replace("{}", &current))?;
+            if time.is_empty() {
+                break;
+            }
+
+            match Entry::resolve_optional_time(&time, Local::now().time()) {
+                Ok(time) => {
+                    entry.time = time;
+                    entry.offset = Some(local_offset());
+                    break;
+                }
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+
+        loop {
+            let target = Entry::normalize_target(&input(tr(
+                "Новая задача (Enter - оставить без изменений): ",
+            ))?);
+            if target.is_empty() {
+                break;
+            }
+
+            match Entry::check_target(&target, max_target_length()) {
+                Ok(()) => {
+                    entry.target = target;
+                    break;
+                }
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+
+        storage.update(index, entry)
+    }
+}
+
+/// Модель отметки записи выполненной, запись можно указать ID
+#[derive(Default)]
+pub struct CompleteEntryModel {
+    pub id: Option<u32>,
+}
+
+impl ModelTrait for CompleteEntryModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let list = storage.read()?;
+
+        let index = match self.id {
+            Some(id) => Some(storage.index_of(id)?),
+            None if list.is_empty() => {
+                println!("{}", tr("Отмечать нечего, расписание пусто."));
+                return Ok(());
+            }
+            None => select_entry(&list, tr("Какую запись отметить?"))?,
+        };
+
+        match index {
+            Some(index) => {
+                let done = list.get(index).is_some_and(|entry| !entry.done());
+                storage.set_done(index, done)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Модель экспорта записей в другие форматы
+#[derive(Default)]
+pub struct ExportModel {
+    pub csv: Option<String>,
+    pub ics: Option<String>,
+}
+
+impl ModelTrait for ExportModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+
+        match (&self.csv, &self.ics) {
+            (Some(path), _) => storage.export_csv(path)?,
+            (None, Some(path)) => storage.export_ics(path)?,
+            (None, None) => Err(AppError::Msg("Не указан формат экспорта."))?,
+        }
+
+        if !quiet() {
+            println!("{}", tr("Экспортировано"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель удаления всех записей с подтверждением
+#[derive(Default)]
+pub struct ClearModel;
+
+impl ModelTrait for ClearModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let count = storage.read()?.len();
+
+        println!(
+            "{}",
+            tr("Записей в планере: {}").replace("{}", &count.to_string())
+        );
+        let answer = input(tr("Удалить все записи? (да/нет): "))?;
+
+        if matches!(answer.to_lowercase().as_str(), "да" | "yes") {
+            storage.clear()?;
+        } else {
+            println!("{}", tr("Отменено"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель импорта записей из другого файла планера
+#[derive(Default)]
+pub struct ImportModel {
+    pub path: Option<String>,
+}
+
+impl ModelTrait for ImportModel {
+    fn exec(&self) -> Result<()> {
+        let path = self
+            .path
+            .as_deref()
+            .ok_or(AppError::Msg("Не указан путь к файлу"))?;
+        let count = CONTAINER.get().unwrap().storage().import(path)?;
+
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Импортировано {} записей").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель переименования категории во всех записях планера
+#[derive(Default)]
+pub struct RecategorizeModel {
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub ignore_case: bool,
+}
+
+impl ModelTrait for RecategorizeModel {
+    fn exec(&self) -> Result<()> {
+        let (Some(old), Some(new)) = (self.old.as_deref(), self.new.as_deref()) else {
+            Err(AppError::Msg("Не указана категория."))?
+        };
+        let count =
+            CONTAINER
+                .get()
+                .unwrap()
+                .storage()
+                .rename_category(old, new, self.ignore_case)?;
+
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Изменено записей: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель переноса просроченных невыполненных записей на сегодня
+#[derive(Default)]
+pub struct RolloverModel;
+
+impl ModelTrait for RolloverModel {
+    fn exec(&self) -> Result<()> {
+        let count = CONTAINER.get().unwrap().storage().rollover(today())?;
+
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Перенесено на сегодня: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель сдвига времени всех записей на несколько минут
+#[derive(Default)]
+pub struct ShiftModel {
+    pub delta: Option<String>,
+    pub range: Option<(Time, Time)>,
+}
+
+impl ModelTrait for ShiftModel {
+    fn exec(&self) -> Result<()> {
+        let delta = self
+            .delta
+            .as_deref()
+            .ok_or(AppError::Msg("Не указан сдвиг."))?;
+        let delta = Self::parse_delta(delta)?;
+        let (count, skipped) = CONTAINER
+            .get()
+            .unwrap()
+            .storage()
+            .shift(delta, self.range)?;
+
+        for target in skipped {
+            eprintln!(
+                "{}",
+                tr("Пропущено, время вышло бы за пределы дня: {}").replace("{}", &target)
+            );
+        }
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Сдвинуто записей: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl ShiftModel {
+    /// Разбор сдвига в минутах: `+15`, `-15`, `15` или `+1:30`
+    pub fn parse_delta(delta: &str) -> Result<i32> {
+        let delta = delta.trim();
+        let (sign, value) = match delta.strip_prefix('-') {
+            Some(value) => (-1, value),
+            None => (1, delta.strip_prefix('+').unwrap_or(delta)),
+        };
+
+        let minutes = match value.split_once(':') {
+            Some((hours, mins)) => {
+                let (Ok(hours), Ok(mins)) = (hours.parse::<i32>(), mins.parse::<i32>()) else {
+                    Err(AppError::Msg("Неверный сдвиг."))?
+                };
+                if !(0..60).contains(&mins) {
+                    Err(AppError::Msg("Неверный сдвиг."))?
+                }
+                hours * 60 + mins
+            }
+            None => value
+                .parse()
+                .map_err(|_| AppError::Msg("Неверный сдвиг."))?,
+        };
+        if !(0..24 * 60).contains(&minutes) {
+            Err(AppError::Msg("Неверный сдвиг."))?
+        }
+
+        Ok(sign * minutes)
+    }
+}
+
+/// Модель переноса выполненных записей в архив
+#[derive(Default)]
+pub struct ArchiveModel;
+
+impl ModelTrait for ArchiveModel {
+    fn exec(&self) -> Result<()> {
+        let container = CONTAINER.get().unwrap();
+        let storage = container.storage();
+        let path = container
+            .archive_path()
+            .map_or_else(|| storage.default_archive_path(), str::to_owned);
+        let archive = Storage::new(&path)
+            .with_backups(0)
+            .with_dry_run(storage.dry_run);
+        let count = storage.archive(&archive)?;
+
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Перенесено в архив: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель заполнения недели по шаблону: каждая строка шаблона
+/// `8:00 зарядка` становится записью на каждый день недели
+#[derive(Default)]
+pub struct TemplateModel {
+    pub name: Option<String>,
+    pub date: Option<String>,
+}
+
+impl ModelTrait for TemplateModel {
+    fn exec(&self) -> Result<()> {
+        let name = self
+            .name
+            .as_deref()
+            .ok_or(AppError::Msg("Не указан шаблон."))?;
+        let date = match self.date.as_deref() {
+            Some(date) => NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+                .map_err(|_| AppError::Msg("Неверная дата."))?,
+            None => today(),
+        };
+
+        let path = Self::path(name)?;
+        log_debug!("чтение шаблона: {}", path.display());
+        let slots = Self::parse(&read_to_string(&path)?)?;
+        let entries = Self::expand(&slots, Self::week_start(date));
+        let count = CONTAINER.get().unwrap().storage().add_missing(entries)?;
+
+        if !quiet() {
+            println!(
+                "{}",
+                tr("Добавлено записей: {}").replace("{}", &count.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl TemplateModel {
+    /// Файл шаблона `templates/<имя>.txt` в текущем каталоге,
+    /// а если его нет - в `~/.config/my_planner/templates`
+    fn path(name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            Err(AppError::Msg("Неверное имя шаблона."))?
+        }
+
+        let file = format!("{}.txt", name);
+        let mut paths = vec![Path::new("templates").join(&file)];
+        if let Some(home) = std::env::var_os("HOME") {
+            paths.push(
+                Path::new(&home)
+                    .join(".config/my_planner/templates")
+                    .join(&file),
+            );
+        }
+
+        Ok(paths
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or(AppError::Msg("Шаблон не найден."))?)
+    }
+
+    /// Разбор шаблона: строки "время задача", пустые строки и строки,
+    /// начинающиеся с `#`, пропускаются
+    pub fn parse(buf: &str) -> Result<Vec<(Time, String)>> {
+        let mut slots = Vec::new();
+
+        for line in buf.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (time, target) = line
+                .split_once(char::is_whitespace)
+                .ok_or(AppError::Msg("Неверная строка шаблона."))?;
+            let target = Entry::normalize_target(target);
+            Entry::check_target(&target, max_target_length())?;
+            slots.push((time.parse()?, target));
+        }
+
+        Ok(slots)
+    }
+
+    /// Понедельник недели, в которую входит `date`
+    pub fn week_start(date: NaiveDate) -> NaiveDate {
+        date - chrono::Days::new(date.weekday().num_days_from_monday().into())
+    }
+
+    /// Записи шаблона на семь дней, начиная с `start`
+    pub fn expand(slots: &[(Time, String)], start: NaiveDate) -> Vec<Box<dyn EntryTrait>> {
+        start
+            .iter_days()
+            .take(7)
+            .flat_map(|date| {
+                slots
+                    .iter()
+                    .map(move |(time, target)| -> Box<dyn EntryTrait> {
+                        Box::new(Entry {
+                            offset: Some(local_offset()),
+                            created_at: timestamp(),
+                            ..Entry::new(&date.format(DATE_FORMAT).to_string(), *time, target)
+                        })
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Модель списка профилей, текущий профиль отмечается звездочкой
+#[derive(Default)]
+pub struct ProfilesModel;
+
+impl ModelTrait for ProfilesModel {
+    fn exec(&self) -> Result<()> {
+        let mut profiles = match read_dir(profile_dir()) {
+            Ok(dir) => dir
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        profiles.sort();
+
+        if profiles.is_empty() {
+            println!("{}", tr("Профилей пока нет."));
+            return Ok(());
+        }
+
+        let active = CONTAINER.get().unwrap().profile();
+        for profile in profiles {
+            let marker = if active == Some(profile.as_str()) {
+                '*'
+            } else {
+                ' '
+            };
+            println!("{} {}", marker, profile);
+        }
+
+        Ok(())
+    }
+}
+
+/// Модель вывода пути к файлу планера и источника, который его задал
+#[derive(Default)]
+pub struct WhereModel;
+
+impl ModelTrait for WhereModel {
+    fn exec(&self) -> Result<()> {
+        let container = CONTAINER.get().unwrap();
+        let path = container.storage().path();
+        // Путь к еще не созданному файлу тоже можно сделать абсолютным
+        let path = std::path::absolute(path).unwrap_or_else(|_| PathBuf::from(path));
+
+        println!("{}", path.display());
+        println!("{}: {}", tr("Источник"), container.path_source().describe());
+
+        Ok(())
+    }
+}
+
+/// Модель обмена местами двух записей на одно время
+#[derive(Default)]
+pub struct SwapModel {
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+impl ModelTrait for SwapModel {
+    fn exec(&self) -> Result<()> {
+        let (Some(first), Some(second)) = (self.first.as_deref(), self.second.as_deref()) else {
+            Err(AppError::Msg("Неверный номер записи."))?
+        };
+
+        CONTAINER
+            .get()
+            .unwrap()
+            .storage()
+            .swap(parse_index(first)?, parse_index(second)?)
+    }
+}
+
+/// Модель объединения двух записей в одну
+#[derive(Default)]
+pub struct MergeModel {
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+impl ModelTrait for MergeModel {
+    fn exec(&self) -> Result<()> {
+        let (Some(first), Some(second)) = (self.first.as_deref(), self.second.as_deref()) else {
+            Err(AppError::Msg("Неверный номер записи."))?
+        };
+
+        CONTAINER
+            .get()
+            .unwrap()
+            .storage()
+            .merge(parse_index(first)?, parse_index(second)?)
+    }
+}
+
+/// Модель отмены последнего изменения планера
+#[derive(Default)]
+pub struct UndoModel;
+
+impl ModelTrait for UndoModel {
+    fn exec(&self) -> Result<()> {
+        CONTAINER.get().unwrap().storage().undo()
+    }
+}
+
+/// Модель восстановления планера из пронумерованной резервной копии
+#[derive(Default)]
+pub struct RestoreModel;
+
+impl ModelTrait for RestoreModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let backups = storage.backups();
+        if backups.is_empty() {
+            println!("{}", tr("Резервных копий нет."));
+            return Ok(());
+        }
+
+        for (number, modified) in &backups {
+            println!("{}. {}", number, modified.format("%Y-%m-%d %H:%M:%S"));
+        }
+
+        let number = input(tr(
+            "Какую копию восстановить? (номер, Enter - пропустить): ",
+        ))?;
+        if number.is_empty() {
+            return Ok(());
+        }
+        let number = number
+            .parse()
+            .map_err(|_| AppError::Msg("Копии с таким номером нет."))?;
+
+        storage.restore(number)
+    }
+}
+
+/// Модель поиска записей по тексту задачи
+#[derive(Default)]
+pub struct SearchEntryModel;
+
+impl ModelTrait for SearchEntryModel {
+    fn exec(&self) -> Result<()> {
+        let query = input(tr("Что ищем?: "))?;
+        if query.is_empty() {
+            return Ok(());
+        }
+
+        let list = CONTAINER.get().unwrap().storage().search(&query)?;
+        if list.is_empty() {
+            println!("{}", tr("Ничего не найдено"));
+            return Ok(());
+        }
+
+        println!("====================================");
+        println!("{}\n\n{}", tr("Найдено:"), ListView::format_entries(&list));
+        println!("====================================");
+
+        Ok(())
+    }
+}
+
+/// Чтение строки пользовательского ввода после вопроса
+fn input(question: &str) -> Result<String> {
+    print!("{}", question);
+    stdout().flush()?;
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+
+    Ok(input.trim().to_owned())
+}
+
+/// Вывод пронумерованного списка записей и выбор одной из них по номеру,
+/// времени или части текста задачи, пустой ввод отменяет выбор
+fn select_entry(list: &[Box<dyn EntryTrait>], question: &str) -> Result<Option<usize>> {
+    for (i, entry) in list.iter().enumerate() {
+        println!("{}. {}", i + 1, entry);
+    }
+
+    let answer = input(
+        &tr("{} (номер, время или часть задачи, Enter - пропустить): ").replace("{}", question),
+    )?;
+    if answer.is_empty() {
+        return Ok(None);
+    }
+    if answer.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(Some(parse_index(&answer)?));
+    }
+
+    // Если под запрос подходят несколько записей, номер уточняется
+    match Storage::matches(list, &answer).as_slice() {
+        [] => Err(AppError::Msg("Запись не найдена."))?,
+        [index] => Ok(Some(*index)),
+        indices => {
+            println!("{}", tr("Подходят несколько записей:"));
+            for index in indices {
+                println!("{}. {}", index + 1, list[*index]);
+            }
+
+            let answer = input(&tr("{} (номер, Enter - пропустить): ").replace("{}", question))?;
+            if answer.is_empty() {
+                return Ok(None);
+            }
+
+            match parse_index(&answer)? {
+                index if indices.contains(&index) => Ok(Some(index)),
+                _ => Err(AppError::Msg("Неверный номер записи."))?,
+            }
+        }
+    }
+}
+
+/// Перевод номера записи, начинающегося с единицы, в индекс списка
+fn parse_index(number: &str) -> Result<usize> {
+    let index = number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .ok_or(AppError::Msg("Неверный номер записи."))?;
+
+    Ok(index)
+}
+
+/// Интрефейс записи для планера
+pub trait EntryTrait: Display {
+    fn date(&self) -> &str;
+
+    fn time(&self) -> Option<Time>;
+
+    fn target(&self) -> &str;
+
+    fn priority(&self) -> u8;
+
+    fn duration_minutes(&self) -> Option<u32>;
+
+    fn done(&self) -> bool;
+
+    fn category(&self) -> &str;
+
+    fn recurrence(&self) -> Option<Recurrence>;
+
+    fn notes(&self) -> &str;
+
+    fn reminder_minutes(&self) -> Option<u32>;
+
+    fn offset(&self) -> Option<FixedOffset>;
+
+    fn order(&self) -> u32;
+
+    fn created_at(&self) -> Option<u64>;
+
+    fn id(&self) -> Option<u32>;
+
+    fn label(&self) -> Option<&str>;
+
+    /// Ключ задачи для поиска повторов: без учета регистра и лишних пробелов
+    fn target_key(&self) -> String {
+        Entry::normalize_target(self.target()).to_lowercase()
+    }
+
+    /// Время окончания задачи по длительности, переход через полночь
+    /// ограничивается временем 23:59
+    fn end_time(&self) -> Option<Time> {
+        let duration = self.duration_minutes()?;
+
+        Some(Time::from_minutes(
+            self.time()?.minutes().saturating_add(duration),
+        ))
+    }
+}
+
+/// Запись для планера
+#[derive(Debug, Clone)]
+pub struct Entry {
+    date: String,
+    time: Option<Time>,
+    target: String,
+    priority: u8,
+    duration_minutes: Option<u32>,
+    done: bool,
+    category: String,
+    recurrence: Option<Recurrence>,
+    notes: String,
+    reminder_minutes: Option<u32>,
+    offset: Option<FixedOffset>,
+    order: u32,
+    created_at: Option<u64>,
+    id: Option<u32>,
+    label: Option<String>,
+}
+
+/// Категория записи по умолчанию
+pub const DEFAULT_CATEGORY: &str = "Общее";
+
+/// Формат хранения даты записи
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Текущая локальная дата с учетом часа начала дня `day_start_hour`
+///
+/// `std::time` не знает о часовом поясе, поэтому локальная дата
+/// берется через `chrono::Local`
+pub fn today() -> NaiveDate {
+    logical_date(Local::now().naive_local(), day_start_hour())
+}
+
+/// Дата логического дня, который начинается в `day_start_hour` часов:
+/// при начале дня в 4:00 момент 2:00 относится еще к предыдущему дню
+pub fn logical_date(now: NaiveDateTime, day_start_hour: u32) -> NaiveDate {
+    (now - chrono::Duration::hours(day_start_hour.into())).date()
+}
+
+/// Настоящий момент начала записи логического дня `date`: время раньше
+/// начала дня приходится уже на следующую календарную дату
+pub fn starts_at(date: NaiveDate, time: Time) -> NaiveDateTime {
+    let start = date.and_time(time.into());
+    if u32::from(time.hours()) < day_start_hour() {
+        start + chrono::Days::new(1)
+    } else {
+        start
+    }
+}
+
+/// Минуты от начала логического дня, по ним записи дня сортируются
+fn day_minutes(time: Time) -> u32 {
+    let shift = day_start_hour() * 60;
+    (time.minutes() + 24 * 60 - shift) % (24 * 60)
+}
+
+/// Текущее смещение местного часового пояса от UTC
+pub fn local_offset() -> FixedOffset {
+    Local::now().offset().fix()
+}
+
+/// Перевод даты и времени из одного смещения часового пояса в другое
+pub fn convert_time(
+    date: NaiveDate,
+    time: Time,
+    from: FixedOffset,
+    to: FixedOffset,
+) -> NaiveDateTime {
+    let utc = date.and_time(time.into()) - from;
+
+    utc + to
+}
+
+/// Завтрашняя дата, переход через конец месяца и года учитывает `chrono`
+pub fn tomorrow() -> NaiveDate {
+    next_day(Local::now().naive_local(), day_start_hour())
+}
+
+/// Дата следующего логического дня после момента `now`
+pub fn next_day(now: NaiveDateTime, day_start_hour: u32) -> NaiveDate {
+    logical_date(now, day_start_hour) + chrono::Days::new(1)
+}
+
+/// Слова, завершающие ввод новых записей
+pub const EXIT_WORDS: [&str; 2] = ["выход", "exit"];
+
+/// Слова, означающие текущее время при вводе
+pub const NOW_WORDS: [&str; 2] = ["сейчас", "now"];
+
+/// Слова, означающие задачу без времени при вводе
+pub const ALL_DAY_WORDS: [&str; 2] = ["весь день", "all day"];
+
+/// Время задачи без времени в текстовом формате хранилища
+const NO_TIME: &str = "-";
+
+/// Приоритет записи по умолчанию
+pub const DEFAULT_PRIORITY: u8 = 3;
+
+/// Наибольшая длина текста задачи по умолчанию
+pub const DEFAULT_MAX_TARGET_LENGTH: usize = 200;
+
+/// Порог числа задач за день для предупреждения по умолчанию
+pub const DEFAULT_CROWDED_THRESHOLD: usize = 10;
+
+/// Число недавних задач, предлагаемых при добавлении записи
+const RECENT_TARGETS: usize = 5;
+
+/// Наибольшая длина метки записи
+pub const MAX_LABEL_LENGTH: usize = 20;
+
+/// Порядок записей с учетом настроек
+fn sort_mode() -> SortMode {
+    CONTAINER
+        .get()
+        .map_or(SortMode::default(), |c| c.sort_mode())
+}
+
+/// Сортировка записей по времени, в порядке добавления список
+/// остается как есть
+fn sort_entries(list: &mut [Box<dyn EntryTrait>]) {
+    match sort_mode() {
+        SortMode::Time => list.sort(),
+        SortMode::Insertion => {}
+        SortMode::Created => list.sort_by(|a, b| newest_first(a.as_ref(), b.as_ref())),
+    }
+}
+
+/// Сравнение записей от новых к старым по времени добавления,
+/// старые записи без времени добавления идут последними
+fn newest_first(a: &dyn EntryTrait, b: &dyn EntryTrait) -> std::cmp::Ordering {
+    b.created_at().cmp(&a.created_at())
+}
+
+/// Текущее время в секундах от начала эпохи Unix
+pub fn timestamp() -> Option<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|elapsed| elapsed.as_secs())
+}
+
+/// Тихий режим без приветствия и подтверждений, флаг --quiet
+fn quiet() -> bool {
+    CONTAINER.get().is_some_and(|c| c.quiet())
+}
+
+/// Наибольшая длина текста задачи с учетом настроек
+fn max_target_length() -> usize {
+    CONTAINER
+        .get()
+        .map_or(DEFAULT_MAX_TARGET_LENGTH, |c| c.max_target_length())
+}
+
+/// Число задач за день, больше которого список предупреждает о загрузке
+fn crowded_threshold() -> usize {
+    CONTAINER
+        .get()
+        .map_or(DEFAULT_CROWDED_THRESHOLD, |c| c.crowded_threshold())
+}
+
+/// Час начала дня из настроек
+fn day_start_hour() -> u32 {
+    CONTAINER.get().map_or(0, |c| c.day_start_hour())
+}
+
+/// Число попыток захватить занятую блокировку планера
+const LOCK_ATTEMPTS: u32 = 40;
+
+/// Число пронумерованных резервных копий планера по умолчанию
+pub const DEFAULT_BACKUPS: usize = 5;
+
+/// Рабочее окно для поиска свободного времени по умолчанию
+pub const DEFAULT_WORK_HOURS: (Time, Time) =
+    (Time { hours: 8, mins: 0 }, Time { hours: 22, mins: 0 });
+
+/// Пауза между попытками захватить блокировку планера
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Число повторов записи файла планера при временной ошибке по умолчанию
+pub const DEFAULT_WRITE_RETRIES: u32 = 3;
+
+/// Пауза перед первым повтором записи, с каждым повтором она растет
+const WRITE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Временная ошибка ввода-вывода, после которой запись стоит повторить
+fn is_transient(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+    )
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Self {
+            date: String::new(),
+            time: None,
+            target: String::new(),
+            priority: DEFAULT_PRIORITY,
+            duration_minutes: None,
+            done: false,
+            category: DEFAULT_CATEGORY.to_owned(),
+            recurrence: None,
+            notes: String::new(),
+            reminder_minutes: None,
+            offset: None,
+            order: 0,
+            created_at: None,
+            id: None,
+            label: None,
+        }
+    }
+}
+
+/// Реализация интерфейса записи для планера
+impl EntryTrait for Entry {
+    fn date(&self) -> &str {
+        &self.date
+    }
+
+    fn time(&self) -> Option<Time> {
+        self.time
+    }
+
+    fn target(&self) -> &str {
+        &self.target
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    fn duration_minutes(&self) -> Option<u32> {
+        self.duration_minutes
+    }
+
+    fn done(&self) -> bool {
+        self.done
+    }
+
+    fn category(&self) -> &str {
+        &self.category
+    }
+
+    fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+
+    fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    fn reminder_minutes(&self) -> Option<u32> {
+        self.reminder_minutes
+    }
+
+    fn offset(&self) -> Option<FixedOffset> {
+        self.offset
+    }
+
+    fn order(&self) -> u32 {
+        self.order
+    }
+
+    fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    fn id(&self) -> Option<u32> {
+        self.id
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+/// Создание записи планера из консольного ввода пользователя
+impl TryFrom<&Stdin> for Entry {
+    type Error = Box<dyn Error>;
+
+    fn try_from(stdin: &Stdin) -> std::result::Result<Self, Self::Error> {
+        Self::read_from(stdin, None)
+    }
+}
+
+/// Создание записи планера из консольного ввода пользователя
+/// с заранее известной датой, тогда вопрос о дне пропускается
+impl Entry {
+    pub fn read_from(stdin: &Stdin, date: Option<NaiveDate>) -> Result<Self> {
+        let mut entry = Self {
+            offset: Some(local_offset()),
+            created_at: timestamp(),
+            ..Self::default()
+        };
+
+        // Недавние задачи можно выбрать номером вместо ввода текста,
+        // поврежденный файл просто не дает подсказок
+        let recent = CONTAINER
+            .get()
+            .and_then(|c| c.storage().recent_targets(RECENT_TARGETS).ok())
+            .unwrap_or_default();
+        if !recent.is_empty() {
+            println!("{}", tr("Недавние задачи (номер - повторить):"));
+            for (i, target) in recent.iter().enumerate() {
+                println!("{}. {}", i + 1, target);
+            }
+        }
+
+        // Ввод завершается словом "выход" или концом ввода (Ctrl-D),
+        // а пустая строка просто запрашивает задачу снова
+        loop {
+            print!("{}", tr("Что планируешь делать? (выход - завершить): "));
+            stdout().flush()?;
+            entry.target = Self::normalize_target(&read_line_or_exit(stdin)?);
+            let picked = entry
+                .target
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| recent.get(n.checked_sub(1)?));
+            if let Some(target) = picked {
+                entry.target = target.clone();
+            }
+
+            if EXIT_WORDS.contains(&entry.target.to_lowercase().as_str()) {
+                Err(AppError::Exit)?
+            }
+
+            match Self::check_target(&entry.target, max_target_length()) {
+                Ok(()) => break,
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+
+        if let Some(date) = date {
+            entry.date = date.format(DATE_FORMAT).to_string();
+        }
+
+        while date.is_none() {
+            print!(
+                "{}",
+                tr("На какой день? (пример 2024-06-01, Enter - сегодня): ")
+            );
+            stdout().flush()?;
+            entry.date = read_line_or_exit(stdin)?;
+
+            if entry.date.trim().is_empty() {
+                entry.date = today().format(DATE_FORMAT).to_string();
+                break;
+            }
+
+            match NaiveDate::parse_from_str(entry.date.trim(), DATE_FORMAT) {
+                Ok(date) => {
+                    entry.date = date.format(DATE_FORMAT).to_string();
+                    break;
+                }
+                Err(..) => eprintln!("{}: {}", tr("Ошибка"), AppError::Msg("Неверная дата.")),
+            }
+        }
+
+        entry.time = Self::read_time(stdin)?;
+        entry.offset = Some(local_offset());
+
+        loop {
+            print!(
+                "{}",
+                tr("Приоритет (1-5, Enter - {})?: ").replace("{}", &DEFAULT_PRIORITY.to_string())
+            );
+            stdout().flush()?;
+            let priority = read_line_or_exit(stdin)?;
+
+            if priority.trim().is_empty() {
+                entry.priority = DEFAULT_PRIORITY;
+                break;
+            }
+
+            match priority.trim().parse::<u8>() {
+                Ok(priority) if (1..=5).contains(&priority) => {
+                    entry.priority = priority;
+                    break;
+                }
+                _ => eprintln!("{}: {}", tr("Ошибка"), AppError::Msg("Неверный приоритет.")),
+            }
+        }
+
+        loop {
+            print!("{}", tr("Сколько минут? (опционально): "));
+            stdout().flush()?;
+            let duration = read_line_or_exit(stdin)?;
+
+            if duration.trim().is_empty() {
+                entry.duration_minutes = None;
+                break;
+            }
+
+            match duration.trim().parse::<u32>() {
+                Ok(duration) if duration > 0 => {
+                    entry.duration_minutes = Some(duration);
+                    break;
+                }
+                _ => eprintln!(
+                    "{}: {}",
+                    tr("Ошибка"),
+                    AppError::Msg("Неверная длительность.")
+                ),
+            }
+        }
+
+        loop {
+            print!("{}", tr("За сколько минут напомнить? (опционально): "));
+            stdout().flush()?;
+            let reminder = read_line_or_exit(stdin)?;
+
+            if reminder.trim().is_empty() {
+                entry.reminder_minutes = None;
+                break;
+            }
+
+            match reminder.trim().parse::<u32>() {
+                Ok(reminder) => {
+                    entry.reminder_minutes = Some(reminder);
+                    break;
+                }
+                _ => eprintln!(
+                    "{}: {}",
+                    tr("Ошибка"),
+                    AppError::Msg("Неверное время напоминания.")
+                ),
+            }
+        }
+
+        loop {
+            print!(
+                "{}",
+                tr("Категория? (работа/дом/..., Enter - {}): ").replace("{}", DEFAULT_CATEGORY)
+            );
+            stdout().flush()?;
+            entry.category = read_line_or_exit(stdin)?;
+            if entry.category.is_empty() {
+                entry.category = DEFAULT_CATEGORY.to_owned();
+            }
+
+            match Self::check_category(&entry.category) {
+                Ok(()) => break,
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+
+        loop {
+            print!(
+                "{}",
+                tr("Метка? (важно, red, green..., Enter - без метки): ")
+            );
+            stdout().flush()?;
+            let label = read_line_or_exit(stdin)?;
+
+            if label.is_empty() {
+                entry.label = None;
+                break;
+            }
+
+            match Self::check_label(&label) {
+                Ok(()) => {
+                    entry.label = Some(label);
+                    break;
+                }
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+
+        loop {
+            print!(
+                "{}",
+                tr("Повторять? (ежедневно/еженедельно, Enter - нет): ")
+            );
+            stdout().flush()?;
+            let recurrence = read_line_or_exit(stdin)?;
+
+            // Еженедельная задача повторяется в день недели своей даты
+            let weekday = NaiveDate::parse_from_str(&entry.date, DATE_FORMAT)
+                .map_or(Weekday::Mon, |date| date.weekday());
+            match recurrence.trim().to_lowercase().as_str() {
+                "" | "нет" | "no" => entry.recurrence = None,
+                "ежедневно" | "daily" => entry.recurrence = Some(Recurrence::Daily),
+                "еженедельно" | "weekly" => {
+                    entry.recurrence = Some(Recurrence::Weekly(weekday))
+                }
+                _ => {
+                    eprintln!("{}: {}", tr("Ошибка"), AppError::Msg("Неверный повтор."));
+                    continue;
+                }
+            }
+            break;
+        }
+
+        println!("{}", tr("Заметки (Enter дважды для завершения):"));
+        let mut notes = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stdin.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            notes.push(line.trim_end().to_owned());
+        }
+        entry.notes = notes.join("\n");
+
+        Ok(entry)
+    }
+}
+
+/// Чтение строки ответа без пробелов по краям, конец ввода (Ctrl-D)
+/// на любом вопросе завершает ввод так же, как слово "выход"
+fn read_line_or_exit(stdin: &Stdin) -> Result<String> {
+    let mut line = String::new();
+    if stdin.read_line(&mut line)? == 0 {
+        // Перевод строки, чтобы вывод не продолжался на строке вопроса
+        println!();
+        Err(AppError::Exit)?
+    }
+
+    Ok(line.trim().to_owned())
+}
+
+/// Время записи в часах и минутах
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    hours: u8,
+    mins: u8,
+}
+
+impl Time {
+    /// Последняя минута суток, дальше время не переносится
+    pub const MAX: Self = Self {
+        hours: 23,
+        mins: 59,
+    };
+
+    /// Создание времени с проверкой часов и минут
+    pub fn new(hours: u8, mins: u8) -> Result<Self> {
+        if hours > 23 || mins > 59 {
+            Err(AppError::Msg("Неверное время."))?
+        }
+
+        Ok(Self { hours, mins })
+    }
+
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    pub fn mins(&self) -> u8 {
+        self.mins
+    }
+
+    /// Число минут от полуночи
+    pub fn minutes(&self) -> u32 {
+        u32::from(self.hours) * 60 + u32::from(self.mins)
+    }
+
+    /// Время через заданное число минут от полуночи, время после 23:59
+    /// ограничивается 23:59
+    pub fn from_minutes(minutes: u32) -> Self {
+        let minutes = minutes.min(Self::MAX.minutes());
+
+        Self {
+            hours: (minutes / 60) as u8,
+            mins: (minutes % 60) as u8,
+        }
+    }
+}
+
+/// Проверка и нормализация введенного пользователем времени
+impl FromStr for Time {
+    type Err = AppError;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        // Суффикс AM/PM переводит 12-часовое время в 24-часовое
+        let input = input.trim().to_lowercase();
+        let (input, pm) = match (input.strip_suffix("am"), input.strip_suffix("pm")) {
+            (Some(input), _) => (input, Some(false)),
+            (_, Some(input)) => (input, Some(true)),
+            _ => (input.as_str(), None),
+        };
+
+        // Точка, пробел и дефис между часами и минутами равносильны двоеточию
+        let time = input
+            .trim()
+            .split(|c: char| matches!(c, ':' | '.' | '-') || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(":")
+            .chars()
+            .filter(|c| matches!(c, '0'..='9' | ':'))
+            .collect::<String>();
+
+        let Some((hours, mins)) = time.split_once(':') else {
+            return Err(AppError::Msg(
+                "Неверное время: укажите часы и минуты, например 9:30.",
+            ));
+        };
+        let invalid = |_| AppError::Msg("Неверное время.");
+        let mut hours: u8 = hours.parse().map_err(invalid)?;
+        let mins: u8 = mins.parse().map_err(invalid)?;
+
+        if let Some(pm) = pm {
+            if !(1..=12).contains(&hours) {
+                return Err(AppError::Msg("Неверное время."));
+            }
+            hours = hours % 12 + if pm { 12 } else { 0 };
+        }
+
+        if hours > 23 || mins > 59 {
+            return Err(AppError::Msg("Неверное время."));
+        }
+
+        Ok(Self { hours, mins })
+    }
+}
+
+/// Запись времени в виде `H:MM`
+impl Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:0>2}", self.hours, self.mins)
+    }
+}
+
+impl From<NaiveTime> for Time {
+    fn from(time: NaiveTime) -> Self {
+        Self {
+            hours: time.hour() as u8,
+            mins: time.minute() as u8,
+        }
+    }
+}
+
+impl From<Time> for NaiveTime {
+    fn from(time: Time) -> Self {
+        NaiveTime::from_hms_opt(time.hours.into(), time.mins.into(), 0).unwrap_or_default()
+    }
+}
+
+/// Ввод времени записи с повтором вопроса до верного ответа,
+/// пустой ответ означает задачу без времени
+impl Entry {
+    pub fn read_time(stdin: &Stdin) -> Result<Option<Time>> {
+        loop {
+            print!(
+                "{}",
+                tr("Во сколько (пример 9:30, +30, сейчас, Enter - весь день): ")
+            );
+            stdout().flush()?;
+            let time = read_line_or_exit(stdin)?;
+
+            match Entry::resolve_optional_time(&time, Local::now().time()) {
+                Ok(time) => return Ok(time),
+                Err(e) => eprintln!("{}: {}", tr("Ошибка"), e),
+            }
+        }
+    }
+}
+
+/// Текст задачи без пробелов по краям, идущие подряд пробелы
+/// заменяются одним, регистр сохраняется
+impl Entry {
+    pub fn normalize_target(target: &str) -> String {
+        target.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Проверка текста задачи: он не пустой, не длиннее `max_length` символов
+/// и без управляющих символов, которые испортили бы файл планера
+impl Entry {
+    pub fn check_target(target: &str, max_length: usize) -> Result<()> {
+        if target.is_empty() {
+            Err(AppError::Msg("Задача не может быть пустой"))?
+        }
+        if target.chars().count() > max_length {
+            Err(AppError::Msg("Слишком длинная задача"))?
+        }
+        if target.chars().any(char::is_control) {
+            Err(AppError::Msg(
+                "Задача не может содержать управляющие символы",
+            ))?
+        }
+
+        Ok(())
+    }
+
+    /// Проверка метки записи: короткое слово без управляющих символов
+    pub fn check_label(label: &str) -> Result<()> {
+        if label.chars().count() > MAX_LABEL_LENGTH {
+            Err(AppError::Msg("Слишком длинная метка"))?
+        }
+        if label.chars().any(char::is_control) {
+            Err(AppError::Msg(
+                "Метка не может содержать управляющие символы",
+            ))?
+        }
+
+        Ok(())
+    }
+
+    /// Проверка названия категории: непустое и без управляющих символов
+    pub fn check_category(category: &str) -> Result<()> {
+        if category.trim().is_empty() {
+            Err(AppError::Msg("Категория не может быть пустой"))?
+        }
+        if category.chars().any(char::is_control) {
+            Err(AppError::Msg(
+                "Категория не может содержать управляющие символы",
+            ))?
+        }
+
+        Ok(())
+    }
+}
+
+/// Значок метки перед задачей, например `[важно]`. Метка с названием
+/// цвета раскрашивается этим цветом, если включены цвета
+pub fn label_badge(label: &str, color: bool) -> String {
+    let badge = format!("[{}]", label);
+    match color::named(label) {
+        Some(code) if color => color::paint(&badge, code),
+        _ => badge,
+    }
+}
+
+/// Разбор относительного времени от текущего момента
+impl Entry {
+    /// Кроме обычного времени принимает "сейчас", "+30" (через 30 минут)
+    /// и "+1:30" (через полтора часа). Время после 23:59 не переносится
+    /// на следующий день, а ограничивается 23:59
+    pub fn resolve_time(input: &str, now: NaiveTime) -> Result<Time> {
+        let input = input.trim().to_lowercase();
+        if NOW_WORDS.contains(&input.as_str()) {
+            return Ok(now.into());
+        }
+
+        let Some(offset) = input.strip_prefix('+') else {
+            return Ok(input.parse()?);
+        };
+
+        let offset = match offset.trim().split_once(':') {
+            Some((hours, mins)) => {
+                let mins: u32 = mins.parse()?;
+                if mins > 59 {
+                    Err(AppError::Msg("Неверное время."))?
+                }
+                hours
+                    .parse::<u32>()?
+                    .saturating_mul(60)
+                    .saturating_add(mins)
+            }
+            None => offset.trim().parse()?,
+        };
+
+        Ok(Time::from_minutes(
+            Time::from(now).minutes().saturating_add(offset),
+        ))
+    }
+
+    /// Разбор времени, которое можно не указывать: пустая строка
+    /// и "весь день" означают задачу без времени
+    pub fn resolve_optional_time(input: &str, now: NaiveTime) -> Result<Option<Time>> {
+        let input = input.trim();
+        if input.is_empty() || ALL_DAY_WORDS.contains(&input.to_lowercase().as_str()) {
+            return Ok(None);
+        }
+
+        Self::resolve_time(input, now).map(Some)
+    }
+}
+
+/// Копирование записи планера из объекта интерфейса
+impl From<&dyn EntryTrait> for Entry {
+    fn from(entry: &dyn EntryTrait) -> Self {
+        Self {
+            date: entry.date().to_owned(),
+            time: entry.time(),
+            target: entry.target().to_owned(),
+            priority: entry.priority(),
+            duration_minutes: entry.duration_minutes(),
+            done: entry.done(),
+            category: entry.category().to_owned(),
+            recurrence: entry.recurrence(),
+            notes: entry.notes().to_owned(),
+            reminder_minutes: entry.reminder_minutes(),
+            offset: entry.offset(),
+            order: entry.order(),
+            created_at: entry.created_at(),
+            id: entry.id(),
+            label: entry.label().map(str::to_owned),
+        }
+    }
+}
+
+/// Получение объекта интерфейса записи для планера
+impl From<Entry> for Box<dyn EntryTrait> {
+    fn from(val: Entry) -> Self {
+        Box::new(val)
+    }
+}
+
+/// Сериализация записи планера в JSON
+impl From<&dyn EntryTrait> for serde_json::Value {
+    fn from(entry: &dyn EntryTrait) -> Self {
+        serde_json::json!({
+            "date": entry.date(),
+            "time": entry.time().map(|time| time.to_string()),
+            "target": entry.target(),
+            "priority": entry.priority(),
+            "duration_minutes": entry.duration_minutes(),
+            "done": entry.done(),
+            "category": entry.category(),
+            "recurrence": entry.recurrence().map(|r| r.to_string()),
+            "notes": entry.notes(),
+            "reminder_minutes": entry.reminder_minutes(),
+            "offset": entry.offset().map(|offset| offset.to_string()),
+            "order": entry.order(),
+            "created_at": entry.created_at(),
+            "id": entry.id(),
+            "label": entry.label(),
+        })
+    }
+}
+
+/// Восстановление записи планера из JSON
+impl TryFrom<&serde_json::Value> for Entry {
+    type Error = Box<dyn Error>;
+
+    fn try_from(value: &serde_json::Value) -> std::result::Result<Self, Self::Error> {
+        let field = |name: &str| value.get(name).and_then(|v| v.as_str()).map(str::to_owned);
+
+        Ok(Self {
+            date: field("date").unwrap_or_default(),
+            time: match field("time") {
+                Some(time) => Some(
+                    time.parse()
+                        .map_err(|_| AppError::Msg("Неверный формат файла."))?,
+                ),
+                None => None,
+            },
+            target: field("target").ok_or(AppError::Msg("Неверный формат файла."))?,
+            priority: value
+                .get("priority")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u8::try_from(v).ok())
+                .unwrap_or(DEFAULT_PRIORITY),
+            duration_minutes: value
+                .get("duration_minutes")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u32::try_from(v).ok()),
+            done: value.get("done").and_then(|v| v.as_bool()).unwrap_or(false),
+            category: field("category").unwrap_or_else(|| DEFAULT_CATEGORY.to_owned()),
+            recurrence: field("recurrence").and_then(|r| r.parse().ok()),
+            notes: field("notes").unwrap_or_default(),
+            reminder_minutes: value
+                .get("reminder_minutes")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u32::try_from(v).ok()),
+            offset: field("offset").and_then(|offset| offset.parse().ok()),
+            order: value
+                .get("order")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u32::try_from(v).ok())
+                .unwrap_or_default(),
+            created_at: value.get("created_at").and_then(|v| v.as_u64()),
+            id: value
+                .get("id")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u32::try_from(v).ok()),
+            label: field("label").filter(|label| !label.is_empty()),
+        })
+    }
+}
+
+/// Отображение записи планера
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Альтернативная форма `{:#}` раскрашивает запись для терминала:
+        // время выделяется цветом, а выполненная запись приглушается целиком
+        let color = f.alternate();
+        if color && self.done {
+            write!(f, "\x1b[{}m", color::DIM)?;
+        }
+
+        // Время записи из другого часового пояса показывается в местном,
+        // а исходное время указывается в скобках
+        let (date, start) = self.local_start();
+        if !date.is_empty() {
+            writeln!(f, "{}: {}", tr("Дата"), date)?;
+        }
+        let mut time = match start {
+            Some(start) => format_time(start),
+            None => tr("весь день").to_owned(),
+        };
+        if let (Some(start), Some(duration)) = (start, self.duration_minutes) {
+            let end = Time::from_minutes(start.minutes().saturating_add(duration));
+            time = format!("{}–{}", time, format_time(end));
+        }
+        if color && !self.done {
+            time = color::paint(&time, color::CYAN);
+        }
+        if let (Some(offset), Some(original)) = (
+            self.offset
+                .filter(|_| start != self.time || date != self.date),
+            self.time,
+        ) {
+            time = format!("{} ({} {})", time, format_time(original), offset);
+        }
+        // Выполненная запись приглушена целиком, поэтому ее метка не раскрашивается
+        let badge = self
+            .label
+            .as_deref()
+            .map(|label| format!("{} ", label_badge(label, color && !self.done)))
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "{}: {}\n{}: [{}] {}{}",
+            tr("Время"),
+            time,
+            tr("Задача"),
+            if self.done { "x" } else { " " },
+            badge,
+            self.target
+        )?;
+        writeln!(f, "{}: {}", tr("Приоритет"), self.priority)?;
+        if let Some(id) = self.id {
+            writeln!(f, "ID: {}", id)?;
+        }
+        if let Some(recurrence) = self.recurrence {
+            writeln!(f, "{}: {}", tr("Повтор"), recurrence.describe())?;
+        }
+        if let Some(reminder) = self.reminder_minutes {
+            writeln!(
+                f,
+                "{}",
+                tr("Напомнить: за {} мин").replace("{}", &reminder.to_string())
+            )?;
+        }
+        if !self.notes.is_empty() {
+            writeln!(f, "{}:", tr("Заметки"))?;
+            for line in self.notes.lines() {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+        if color && self.done {
+            write!(f, "\x1b[0m")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Проверка, что запись с датой и временем начинается раньше `now`,
+/// записи без даты или без времени прошедшими не считаются
+impl Entry {
+    pub fn is_past(&self, now: NaiveDateTime) -> bool {
+        let Ok(date) = NaiveDate::parse_from_str(&self.date, DATE_FORMAT) else {
+            return false;
+        };
+
+        self.time.is_some_and(|time| starts_at(date, time) < now)
+    }
+}
+
+/// Дата и время начала записи в местном часовом поясе
+impl Entry {
+    pub fn local_start(&self) -> (String, Option<Time>) {
+        // Задача без времени относится к дню целиком и не переводится
+        let (Some(offset), Some(time)) = (
+            self.offset.filter(|offset| *offset != local_offset()),
+            self.time,
+        ) else {
+            return (self.date.clone(), self.time);
+        };
+
+        // Запись без даты относится к любому дню, поэтому переводится
+        // только время
+        let date = NaiveDate::parse_from_str(&self.date, DATE_FORMAT).unwrap_or_else(|_| today());
+        let local = convert_time(date, time, offset, local_offset());
+        let date = match self.date.as_str() {
+            "" => String::new(),
+            _ => local.date().format(DATE_FORMAT).to_string(),
+        };
+
+        (date, Some(local.time().into()))
+    }
+}
+
+/// Создание записи планера с датой, временем и задачей,
+/// остальные поля получают значения по умолчанию
+impl Entry {
+    pub fn new(date: &str, time: Time, target: &str) -> Self {
+        Self {
+            date: date.to_owned(),
+            time: Some(time),
+            target: target.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Сохранение записи планера
+impl Entry {
+    pub fn save(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        storage.save(self.clone().into())?;
+
+        Ok(())
+    }
+}
+
+/// Правило повторения записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recurrence {
+    Daily,
+    Weekly(Weekday),
+}
+
+impl Recurrence {
+    /// Проверка, приходится ли повторение на дату, повторения начинаются
+    /// с даты самой записи, а запись без даты повторяется всегда
+    pub fn occurs_on(&self, start: &str, date: NaiveDate) -> bool {
+        if NaiveDate::parse_from_str(start, DATE_FORMAT).is_ok_and(|start| date < start) {
+            return false;
+        }
+
+        match self {
+            Self::Daily => true,
+            Self::Weekly(weekday) => date.weekday() == *weekday,
+        }
+    }
+
+    /// Описание правила для пользователя
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Daily => tr("ежедневно").to_owned(),
+            Self::Weekly(weekday) => {
+                format!("{}, {}", tr("еженедельно"), weekday_name(*weekday))
+            }
+        }
+    }
+}
+
+/// Краткое название дня недели
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    let names = ["пн", "вт", "ср", "чт", "пт", "сб", "вс"];
+    tr(names[weekday.num_days_from_monday() as usize])
+}
+
+/// Запись правила в хранилище: `daily` или `weekly mon`
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly(weekday) => write!(f, "weekly {}", weekday.to_string().to_lowercase()),
+        }
+    }
+}
+
+/// Чтение правила из хранилища
+impl FromStr for Recurrence {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().split_once(' ') {
+            None if s.trim() == "daily" => Ok(Self::Daily),
+            Some(("weekly", weekday)) => Ok(Self::Weekly(
+                weekday
+                    .parse()
+                    .map_err(|_| AppError::Msg("Неверный повтор."))?,
+            )),
+            _ => Err(AppError::Msg("Неверный повтор."))?,
+        }
+    }
+}
+
+/// Разворачивание повторяющихся записей в конкретные повторения
+/// на каждый день диапазона, обычные записи вне диапазона отбрасываются,
+/// а записи без даты остаются как есть
+pub fn expand_range(
+    list: Vec<Box<dyn EntryTrait>>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<Box<dyn EntryTrait>> {
+    let mut expanded: Vec<Box<dyn EntryTrait>> = Vec::new();
+    for entry in list {
+        match entry.recurrence() {
+            Some(recurrence) => {
+                for date in from.iter_days().take_while(|date| *date <= to) {
+                    if recurrence.occurs_on(entry.date(), date) {
+                        let mut occurrence = Entry::from(entry.as_ref());
+                        occurrence.date = date.format(DATE_FORMAT).to_string();
+                        expanded.push(occurrence.into());
+                    }
+                }
+            }
+            None => {
+                let in_range = NaiveDate::parse_from_str(entry.date(), DATE_FORMAT)
+                    .is_ok_and(|date| from <= date && date <= to);
+                if entry.date().is_empty() || in_range {
+                    expanded.push(entry);
+                }
+            }
+        }
+    }
+
+    sort_entries(&mut expanded);
+    expanded
+}
+
+/// Представление списка записей
+#[derive(Default)]
+pub struct ListView {
+    day: Option<NaiveDate>,
+    category: Option<String>,
+    now: Option<NaiveDateTime>,
+    color: bool,
+    compact: bool,
+    reverse: bool,
+}
+
+impl ListView {
+    /// Представление только сегодняшних записей,
+    /// записи без даты относятся к любому дню и тоже показываются
+    pub fn today() -> Self {
+        Self::on(today())
+    }
+
+    /// Представление только завтрашних записей
+    pub fn tomorrow() -> Self {
+        Self::on(tomorrow())
+    }
+
+    /// Представление записей одного дня с повторениями на этот день
+    pub fn on(day: NaiveDate) -> Self {
+        Self {
+            day: Some(day),
+            ..Default::default()
+        }
+    }
+
+    /// Ограничение представления одной категорией
+    pub fn with_category(self, category: Option<String>) -> Self {
+        Self { category, ..self }
+    }
+
+    /// Отметка скорых и прошедших записей относительно текущего момента
+    pub fn with_now(self, now: Option<NaiveDateTime>) -> Self {
+        Self { now, ..self }
+    }
+
+    /// Раскраска вывода ANSI кодами
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Краткий вид: одна строка на запись без разделителей
+    pub fn with_compact(self, compact: bool) -> Self {
+        Self { compact, ..self }
+    }
+
+    /// Обратный порядок записей внутри групп, от поздних к ранним. Группы
+    /// не меняются местами, поэтому записи без времени остаются последними
+    pub fn with_reverse(self, reverse: bool) -> Self {
+        Self { reverse, ..self }
+    }
+}
+
+impl Display for ListView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Ошибка чтения показывается вместо списка, а не обрывает вывод
+        match CONTAINER.get().unwrap().storage().read() {
+            Ok(list) => write!(f, "{}", self.render(list)),
+            Err(e) => writeln!(f, "{}: {}", tr("Ошибка"), e),
+        }
+    }
+}
+
+/// Отображение переданного списка записей с учетом фильтров представления
+impl ListView {
+    pub fn render(&self, list: Vec<Box<dyn EntryTrait>>) -> String {
+        self.render_pages(list, usize::MAX).remove(0)
+    }
+
+    /// Отображение списка страницами по `page_size` записей, итог в подвале
+    /// считается по всему списку, а номер страницы выводится, если их больше одной
+    pub fn render_pages(&self, list: Vec<Box<dyn EntryTrait>>, page_size: usize) -> Vec<String> {
+        let mut list = self.select(list);
+        if let Some(day) = self.day.filter(|_| list.is_empty()) {
+            let message = if day == today() {
+                tr("На сегодня ничего не запланировано").to_owned()
+            } else if day == tomorrow() {
+                tr("На завтра ничего не запланировано").to_owned()
+            } else {
+                tr("На {} ничего не запланировано")
+                    .replace("{}", &day.format(DATE_FORMAT).to_string())
+            };
+            return vec![format!("{}\n", message)];
+        }
+
+        // Пустое расписание показывается подсказкой внутри рамки,
+        // а не пустым заголовком
+        if list.is_empty() {
+            return vec![Self::frame(&format!(
+                "{}\n",
+                tr("Расписание пусто. Добавь первую задачу!")
+            ))];
+        }
+
+        // Страницы режутся в том же порядке, в каком записи выводятся по группам,
+        // задачи без времени идут последними
+        let mut summary = Self::summary(&list);
+        if let Some(warning) = self.crowded_warning(list.len(), crowded_threshold()) {
+            summary = format!("{}\n{}", summary, warning);
+        }
+        // Обратный порядок не меняет групп, а равные записи после
+        // разворота списка тоже идут в обратном порядке
+        if self.reverse {
+            list.reverse();
+        }
+        list.sort_by(|a, b| {
+            let group = (a.time().is_none(), a.category()).cmp(&(b.time().is_none(), b.category()));
+            group.then(self.entry_order()(a, b))
+        });
+        let pages = paginate(list, page_size);
+        let count = pages.len();
+
+        pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, page)| {
+                let mut body = format!("{}\n{}\n", self.format_groups(page), summary);
+                if count > 1 {
+                    body += &tr("Страница {}/{}")
+                        .replacen("{}", &(i + 1).to_string(), 1)
+                        .replacen("{}", &count.to_string(), 1);
+                    body += "\n";
+                }
+                Self::frame(&body)
+            })
+            .collect()
+    }
+
+    /// Порядок записей внутри группы по настройке порядка, с `--reverse`
+    /// обратный. В порядке добавления записи не сравниваются и остаются
+    /// в порядке списка
+    fn entry_order(
+        &self,
+    ) -> impl Fn(&Box<dyn EntryTrait>, &Box<dyn EntryTrait>) -> std::cmp::Ordering + '_ {
+        move |a, b| {
+            let order = match sort_mode() {
+                SortMode::Time => a.cmp(b),
+                SortMode::Insertion => std::cmp::Ordering::Equal,
+                SortMode::Created => newest_first(a.as_ref(), b.as_ref()),
+            };
+            if self.reverse {
+                order.reverse()
+            } else {
+                order
+            }
+        }
+    }
+
+    /// Рамка с заголовком вокруг содержимого списка
+    fn frame(body: &str) -> String {
+        let mut output = String::new();
+        output += "====================================\n";
+        output += &format!("{}\n\n{}\n", tr("Мое расписание:"), body);
+        output += "====================================\n";
+
+        output
+    }
+}
+
+/// Разбиение списка на страницы не больше `size` элементов,
+/// пустой список дает одну пустую страницу
+pub fn paginate<T>(list: Vec<T>, size: usize) -> Vec<Vec<T>> {
+    let size = size.max(1);
+    let mut pages = vec![Vec::new()];
+    for item in list {
+        if pages.last().is_some_and(|page| page.len() >= size) {
+            pages.push(Vec::new());
+        }
+        pages.last_mut().unwrap().push(item);
+    }
+
+    pages
+}
+
+/// Отбор записей по фильтрам представления
+impl ListView {
+    pub fn select(&self, mut list: Vec<Box<dyn EntryTrait>>) -> Vec<Box<dyn EntryTrait>> {
+        if let Some(day) = self.day {
+            list = expand_range(list, day, day);
+        }
+
+        if let Some(category) = &self.category {
+            list.retain(|entry| entry.category() == category);
+        }
+
+        list
+    }
+}
+
+/// Представление записей массивом JSON для других программ,
+/// без рамок и заголовков
+#[derive(Default, Debug, Clone)]
+pub struct JsonView;
+
+impl JsonView {
+    pub fn render(&self, list: &[Box<dyn EntryTrait>]) -> String {
+        let list = list
+            .iter()
+            .map(|entry| entry.as_ref().into())
+            .collect::<Vec<serde_json::Value>>();
+
+        format!("{:#}", serde_json::Value::Array(list))
+    }
+}
+
+/// Форматирование записей для вывода с разделителями
+impl ListView {
+    pub fn format_entries(list: &[Box<dyn EntryTrait>]) -> String {
+        Self::default().format_list(list)
+    }
+
+    /// Форматирование записей с учетом настроек представления: отметки
+    /// скорых и прошедших записей и раскраски, прошедшие записи
+    /// выделяются красным
+    pub fn format_list(&self, list: &[Box<dyn EntryTrait>]) -> String {
+        if self.compact {
+            return self.format_compact(list);
+        }
+
+        list.iter()
+            .map(|entry| {
+                let status = self.now.and_then(|now| Self::status(entry.as_ref(), now));
+                match status {
+                    Some(status) if self.color && status == "прошло" => {
+                        color::paint(&format!("{}{}\n", entry, tr(status)), color::RED)
+                    }
+                    Some(status) if self.color => format!("{:#}{}\n", entry, tr(status)),
+                    Some(status) => format!("{}{}\n", entry, tr(status)),
+                    None if self.color => format!("{:#}", entry),
+                    None => entry.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("--------------------------\n")
+    }
+
+    /// Краткий вид записей: время, отметка и задача в одной строке,
+    /// время выравнивается по самому длинному в списке. Дата
+    /// показывается, только если представление не ограничено одним днем
+    fn format_compact(&self, list: &[Box<dyn EntryTrait>]) -> String {
+        let rows = list
+            .iter()
+            .map(|entry| {
+                let (date, start) = Entry::from(entry.as_ref()).local_start();
+                let mut time = start.map_or_else(|| tr("весь день").to_owned(), format_time);
+                if let (Some(start), Some(duration)) = (start, entry.duration_minutes()) {
+                    let end = Time::from_minutes(start.minutes().saturating_add(duration));
+                    time = format!("{}–{}", time, format_time(end));
+                }
+                if self.day.is_none() && !date.is_empty() {
+                    time = format!("{} {}", date, time);
+                }
+                (time, entry)
+            })
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(time, _)| time.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        rows.into_iter()
+            .map(|(time, entry)| {
+                let time = format!("{:<width$}", time, width = width);
+                let time = match self.color && !entry.done() {
+                    true => color::paint(&time, color::CYAN),
+                    false => time,
+                };
+                let badge = entry
+                    .label()
+                    .map(|label| format!("{} ", label_badge(label, self.color && !entry.done())))
+                    .unwrap_or_default();
+                let mut line = format!(
+                    "{}  [{}] {}{}",
+                    time,
+                    if entry.done() { "x" } else { " " },
+                    badge,
+                    entry.target()
+                );
+                if let Some(status) = self.now.and_then(|now| Self::status(entry.as_ref(), now)) {
+                    line = format!("{}  {}", line, tr(status));
+                    if self.color && status == "прошло" {
+                        line = color::paint(&line, color::RED);
+                    }
+                } else if self.color && entry.done() {
+                    line = color::paint(&line, color::DIM);
+                }
+                line + "\n"
+            })
+            .collect()
+    }
+
+    /// Состояние невыполненной записи относительно текущего момента:
+    /// "скоро", если до начала осталось не больше времени напоминания,
+    /// и "прошло", если запись уже закончилась. Запись без даты
+    /// относится к сегодняшнему дню, а задача без времени отметок не получает
+    pub fn status(entry: &dyn EntryTrait, now: NaiveDateTime) -> Option<&'static str> {
+        if entry.done() {
+            return None;
+        }
+
+        let date = match entry.date() {
+            "" => logical_date(now, day_start_hour()),
+            date => NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?,
+        };
+        let start = starts_at(date, entry.time()?);
+        let end = entry.end_time().map_or(start, |end| starts_at(date, end));
+
+        if end < now {
+            return Some("прошло");
+        }
+
+        let reminder = chrono::Duration::minutes(entry.reminder_minutes()?.into());
+        if start - reminder <= now && now <= start {
+            return Some("⏰ скоро");
+        }
+
+        None
+    }
+
+    /// Предупреждение о загруженном дне, если задач на день больше `threshold`.
+    /// Выводится только в списке одного дня
+    pub fn crowded_warning(&self, count: usize, threshold: usize) -> Option<String> {
+        let day = self.day.filter(|_| count > threshold)?;
+        let warning = if day == today() {
+            tr("⚠ Много задач на сегодня ({})").to_owned()
+        } else if day == tomorrow() {
+            tr("⚠ Много задач на завтра ({})").to_owned()
+        } else {
+            tr("⚠ Много задач на {} ({})").replacen("{}", &day.format(DATE_FORMAT).to_string(), 1)
+        };
+
+        Some(warning.replacen("{}", &count.to_string(), 1))
+    }
+
+    /// Итоговые строки с числом задач, выполненных задач и полосой прогресса
+    pub fn summary(list: &[Box<dyn EntryTrait>]) -> String {
+        let done = list.iter().filter(|entry| entry.done()).count();
+
+        let count = tr("Всего задач: {} (выполнено: {})")
+            .replacen("{}", &list.len().to_string(), 1)
+            .replacen("{}", &done.to_string(), 1);
+
+        format!("{}\n{}", count, Self::progress(done, list.len()))
+    }
+
+    /// Полоса прогресса из десяти делений, например `[#####-----] 50%`,
+    /// без задач вместо нее выводится "нет задач"
+    pub fn progress(done: usize, total: usize) -> String {
+        if total == 0 {
+            return tr("нет задач").to_owned();
+        }
+
+        let filled = done.min(total) * 10 / total;
+        format!(
+            "[{}{}] {}%",
+            "#".repeat(filled),
+            "-".repeat(10 - filled),
+            done.min(total) * 100 / total
+        )
+    }
+
+    /// Форматирование записей, сгруппированных по категориям
+    pub fn format_groups(&self, list: Vec<Box<dyn EntryTrait>>) -> String {
+        let mut groups: BTreeMap<String, Vec<Box<dyn EntryTrait>>> = BTreeMap::new();
+        let mut untimed = Vec::new();
+        for entry in list {
+            if entry.time().is_none() {
+                untimed.push(entry);
+                continue;
+            }
+            groups
+                .entry(entry.category().to_owned())
+                .or_default()
+                .push(entry);
+        }
+
+        // Задачи без времени собираются в последнюю группу
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        if !untimed.is_empty() {
+            groups.push((tr("Без времени").to_owned(), untimed));
+        }
+
+        groups
+            .into_iter()
+            .map(|(category, mut list)| {
+                list.sort_by(self.entry_order());
+                format!("[{}]\n{}", category, self.format_list(&list))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Контейнер для разрешения зависимостей
+pub struct Container {
+    storage: Storage,
+    list_view: ListView,
+    time_format: TimeFormat,
+    lang: Lang,
+    max_target_length: usize,
+    day_start_hour: u32,
+    work_hours: (Time, Time),
+    crowded_threshold: usize,
+    quiet: bool,
+    sort_mode: SortMode,
+    archive_path: Option<String>,
+    profile: Option<String>,
+    path_source: PathSource,
+}
+
+/// Создание контейнера с настройками из файла config.toml
+impl Default for Container {
+    fn default() -> Self {
+        Self::with_config(Config::load())
+    }
+}
+
+/// Создание контейнера с указанными настройками,
+/// переменные окружения имеют приоритет над файлом настроек
+impl Container {
+    pub fn with_config(config: Config) -> Self {
+        let env_path = std::env::var("MY_PLANNER_PATH")
+            .ok()
+            .map(|path| path.trim().to_owned())
+            .filter(|path| !path.is_empty());
+        let (path, path_source) = match (env_path, config.storage_path) {
+            (Some(path), _) => (path, PathSource::Env),
+            (None, Some(path)) => (path, PathSource::Config),
+            (None, None) => ("./my-planner.txt".to_owned(), PathSource::Default),
+        };
+        let max_entries = std::env::var("MY_PLANNER_MAX_ENTRIES")
+            .ok()
+            .and_then(|max| max.trim().parse().ok())
+            .or(config.max_entries);
+        let backups = std::env::var("MY_PLANNER_BACKUPS")
+            .ok()
+            .and_then(|count| count.trim().parse().ok())
+            .or(config.backups)
+            .unwrap_or(DEFAULT_BACKUPS);
+        let write_retries = std::env::var("MY_PLANNER_WRITE_RETRIES")
+            .ok()
+            .and_then(|count| count.trim().parse().ok())
+            .or(config.write_retries)
+            .unwrap_or(DEFAULT_WRITE_RETRIES);
+        let text_mirror = std::env::var("MY_PLANNER_TEXT_MIRROR")
+            .ok()
+            .map(|value| matches!(value.trim(), "1" | "true"))
+            .or(config.text_mirror)
+            .unwrap_or_default();
+        let create_dirs = std::env::var("MY_PLANNER_CREATE_DIRS")
+            .ok()
+            .map(|value| !matches!(value.trim(), "0" | "false"))
+            .or(config.create_dirs)
+            .unwrap_or(true);
+        let storage = Storage::new(&path)
+            .with_max_entries(max_entries)
+            .with_backups(backups)
+            .with_write_retries(write_retries)
+            .with_text_mirror(text_mirror)
+            .with_create_dirs(create_dirs);
+        let max_target_length = std::env::var("MY_PLANNER_MAX_TARGET_LENGTH")
+            .ok()
+            .and_then(|max| max.trim().parse().ok())
+            .or(config.max_target_length)
+            .unwrap_or(DEFAULT_MAX_TARGET_LENGTH);
+        let day_start_hour = std::env::var("MY_PLANNER_DAY_START_HOUR")
+            .ok()
+            .and_then(|hour| hour.trim().parse().ok())
+            .or(config.day_start_hour)
+            .filter(|hour| *hour < 24)
+            .unwrap_or_default();
+        // Рабочее окно задается границами `work_start` и `work_end`
+        let bound = |name: &str, config: Option<Time>, default: Time| {
+            std::env::var(name)
+                .ok()
+                .and_then(|time| time.trim().parse().ok())
+                .or(config)
+                .unwrap_or(default)
+        };
+        let work_hours = (
+            bound(
+                "MY_PLANNER_WORK_START",
+                config.work_start,
+                DEFAULT_WORK_HOURS.0,
+            ),
+            bound("MY_PLANNER_WORK_END", config.work_end, DEFAULT_WORK_HOURS.1),
+        );
+        let crowded_threshold = std::env::var("MY_PLANNER_CROWDED_THRESHOLD")
+            .ok()
+            .and_then(|count| count.trim().parse().ok())
+            .or(config.crowded_threshold)
+            .unwrap_or(DEFAULT_CROWDED_THRESHOLD);
+        let list_view = ListView::default();
+        let time_format = std::env::var("MY_PLANNER_TIME_FORMAT")
+            .ok()
+            .map(|name| TimeFormat::from_setting(name.trim()))
+            .or(config.time_format)
+            .unwrap_or_default();
+        let lang = Lang::from_env().or(config.lang).unwrap_or_default();
+        let archive_path = std::env::var("MY_PLANNER_ARCHIVE_PATH")
+            .ok()
+            .map(|path| path.trim().to_owned())
+            .filter(|path| !path.is_empty())
+            .or(config.archive_path);
+        let sort_mode = std::env::var("MY_PLANNER_SORT_MODE")
+            .ok()
+            .and_then(|name| SortMode::from_name(name.trim()))
+            .or(config.sort_mode)
+            .unwrap_or_default();
+
+        Self {
+            storage,
+            list_view,
+            time_format,
+            lang,
+            max_target_length,
+            day_start_hour,
+            work_hours,
+            crowded_threshold,
+            quiet: false,
+            sort_mode,
+            archive_path,
+            profile: None,
+            path_source,
+        }
+    }
+}
+
+/// Каталог файлов профилей: `$XDG_DATA_HOME/my_planner`, а если
+/// переменная не задана - `~/.local/share/my_planner`
+pub fn profile_dir() -> PathBuf {
+    let data = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    data.join("my_planner")
+}
+
+/// Путь к файлу профиля, например `work` - `~/.local/share/my_planner/work.txt`
+pub fn profile_path(name: &str) -> String {
+    profile_dir()
+        .join(format!("{}.txt", name))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Имя профиля становится именем файла, поэтому разделители
+/// путей и имена, начинающиеся с точки, не допускаются
+pub fn check_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        Err(AppError::Msg("Неверное имя профиля."))?
+    }
+
+    Ok(())
+}
+
+/// Настройки приложения из файла config.toml
+#[derive(Default, Debug, Clone)]
+pub struct Config {
+    pub storage_path: Option<String>,
+    pub lang: Option<Lang>,
+    pub time_format: Option<TimeFormat>,
+    pub max_entries: Option<usize>,
+    pub max_target_length: Option<usize>,
+    pub crowded_threshold: Option<usize>,
+    pub day_start_hour: Option<u32>,
+    pub work_start: Option<Time>,
+    pub work_end: Option<Time>,
+    pub backups: Option<usize>,
+    pub write_retries: Option<u32>,
+    pub text_mirror: Option<bool>,
+    pub create_dirs: Option<bool>,
+    pub sort_mode: Option<SortMode>,
+    pub archive_path: Option<String>,
+}
+
+impl Config {
+    /// Загрузка настроек из `./config.toml`, а если его нет -
+    /// из `~/.config/my_planner/config.toml`
+    pub fn load() -> Self {
+        let mut paths = vec!["./config.toml".to_owned()];
+        if let Ok(home) = std::env::var("HOME") {
+            paths.push(format!("{}/.config/my_planner/config.toml", home));
+        }
+
+        let Some(path) = paths.into_iter().find(|path| Path::new(path).is_file()) else {
+            return Self::default();
+        };
+
+        log_debug!("чтение настроек: {}", path);
+        match read_to_string(&path)
+            .map_err(|e| e.into())
+            .and_then(|buf| Self::parse(&buf))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{}: {}: {}", tr("Ошибка"), path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Разбор настроек, неизвестные параметры пропускаются с предупреждением
+    pub fn parse(buf: &str) -> Result<Self> {
+        let table: toml::Table = buf.parse()?;
+        let mut config = Self::default();
+
+        for (key, value) in &table {
+            // Формат времени можно указать и строкой, и числом
+            let value = match value {
+                toml::Value::String(value) => value.to_owned(),
+                value => value.to_string(),
+            };
+
+            match key.as_str() {
+                "storage_path" => config.storage_path = Some(value),
+                "lang" => config.lang = Lang::from_name(&value),
+                "time_format" => config.time_format = Some(TimeFormat::from_setting(&value)),
+                "max_entries" => config.max_entries = value.parse().ok(),
+                "max_target_length" => config.max_target_length = value.parse().ok(),
+                "crowded_threshold" => config.crowded_threshold = value.parse().ok(),
+                "day_start_hour" => config.day_start_hour = value.parse().ok(),
+                "work_start" => config.work_start = value.parse().ok(),
+                "work_end" => config.work_end = value.parse().ok(),
+                "backups" => config.backups = value.parse().ok(),
+                "write_retries" => config.write_retries = value.parse().ok(),
+                "text_mirror" => config.text_mirror = value.parse().ok(),
+                "create_dirs" => config.create_dirs = value.parse().ok(),
+                "sort_mode" => config.sort_mode = SortMode::from_name(&value),
+                "archive_path" => config.archive_path = Some(value),
+                key => eprintln!(
+                    "{}",
+                    tr("Внимание: неизвестный параметр настроек: {}").replace("{}", key)
+                ),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Создание контейнера с учетом аргументов командной строки
+impl From<&Args> for Container {
+    fn from(args: &Args) -> Self {
+        let mut container = Self::default();
+        if let Some(lang) = args.lang {
+            container.lang = lang;
+        }
+        // Файл из --file важнее профиля, каталог профилей создается
+        // при первой записи в профиль
+        let path = match (&args.file, &args.profile) {
+            (Some(path), _) => {
+                container.path_source = PathSource::Flag;
+                Some(path.clone())
+            }
+            (None, Some(profile)) => {
+                container.path_source = PathSource::Profile;
+                Some(profile_path(profile))
+            }
+            (None, None) => None,
+        };
+        let storage = match path {
+            Some(path) => Storage::new(&path)
+                .with_max_entries(container.storage.max_entries)
+                .with_backups(container.storage.keep_backups)
+                .with_write_retries(container.storage.write_retries)
+                .with_text_mirror(container.storage.text_mirror)
+                .with_create_dirs(
+                    container.storage.create_dirs || container.path_source == PathSource::Profile,
+                ),
+            None => container.storage,
+        };
+        container.profile = args.profile.clone();
+        container.storage = storage
+            .with_dry_run(args.dry_run)
+            .with_quiet(args.quiet)
+            .with_unique(args.if_not_exists)
+            .with_strict(args.strict);
+        container.quiet = args.quiet;
+        if let Some(sort_mode) = args.sort_mode {
+            container.sort_mode = sort_mode;
+        }
+
+        container
+    }
+}
+
+/// Получение объектов из контейнера
+impl Container {
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    pub fn list_view(&self) -> &ListView {
+        &self.list_view
+    }
+
+    pub fn time_format(&self) -> &TimeFormat {
+        &self.time_format
+    }
+
+    pub fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    pub fn max_target_length(&self) -> usize {
+        self.max_target_length
+    }
+
+    pub fn day_start_hour(&self) -> u32 {
+        self.day_start_hour
+    }
+
+    pub fn work_hours(&self) -> (Time, Time) {
+        self.work_hours
+    }
+
+    pub fn crowded_threshold(&self) -> usize {
+        self.crowded_threshold
+    }
+
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    pub fn sort_mode(&self) -> SortMode {
+        self.sort_mode
+    }
+
+    pub fn archive_path(&self) -> Option<&str> {
+        self.archive_path.as_deref()
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn path_source(&self) -> PathSource {
+        self.path_source
+    }
+}
+
+/// Откуда взят путь к файлу планера
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PathSource {
+    /// Путь по умолчанию `./my-planner.txt`
+    #[default]
+    Default,
+    /// Переменная окружения `MY_PLANNER_PATH`
+    Env,
+    /// Параметр `storage_path` файла настроек
+    Config,
+    /// Флаг `--file`
+    Flag,
+    /// Флаг `--profile`
+    Profile,
+}
+
+impl PathSource {
+    /// Описание источника для пользователя
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Default => tr("по умолчанию"),
+            Self::Env => tr("переменная окружения MY_PLANNER_PATH"),
+            Self::Config => tr("параметр storage_path в config.toml"),
+            Self::Flag => tr("флаг --file"),
+            Self::Profile => tr("флаг --profile"),
+        }
+    }
+}
+
+/// Отображение времени в формате из настроек, в файле планера
+/// время всегда хранится как `9:30`
+pub fn format_time(time: Time) -> String {
+    match CONTAINER.get() {
+        Some(container) => container.time_format().format(time),
+        None => time.to_string(),
+    }
+}
+
+/// Формат отображения времени
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum TimeFormat {
+    #[default]
+    H24,
+    H12,
+    /// Шаблон вида `{h}:{mm}`: `{h}` и `{m}` - часы и минуты без нуля
+    /// впереди, `{hh}` и `{mm}` - двумя цифрами
+    Template(String),
+}
+
+impl TimeFormat {
+    /// Получение формата по названию из настроек (`24`, `12` или шаблон)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "24" | "24h" => Some(Self::H24),
+            "12" | "12h" => Some(Self::H12),
+            _ => Self::template(name),
+        }
+    }
+
+    /// Получение формата из настроек, при неверном значении выводится
+    /// предупреждение и используется формат по умолчанию
+    pub fn from_setting(value: &str) -> Self {
+        Self::from_name(value).unwrap_or_else(|| {
+            eprintln!(
+                "{}",
+                tr("Внимание: неверный формат времени: {}, используется 9:30").replace("{}", value)
+            );
+            Self::default()
+        })
+    }
+
+    /// Проверка шаблона: допускаются только известные поля,
+    /// и в шаблоне должны быть и часы, и минуты
+    fn template(template: &str) -> Option<Self> {
+        let (mut hours, mut mins) = (false, false);
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}')? + start;
+            match &rest[start + 1..end] {
+                "h" | "hh" => hours = true,
+                "m" | "mm" => mins = true,
+                _ => return None,
+            }
+            rest = &rest[end + 1..];
+        }
+
+        (hours && mins && !rest.contains('}')).then(|| Self::Template(template.to_owned()))
+    }
+
+    /// Отображение времени в выбранном формате
+    pub fn format(&self, time: Time) -> String {
+        match self {
+            Self::H24 => time.to_string(),
+            Self::Template(template) => template
+                .replace("{hh}", &format!("{:0>2}", time.hours()))
+                .replace("{h}", &time.hours().to_string())
+                .replace("{mm}", &format!("{:0>2}", time.mins()))
+                .replace("{m}", &time.mins().to_string()),
+            Self::H12 => {
+                let suffix = if time.hours() < 12 { "AM" } else { "PM" };
+                let hours = match time.hours() % 12 {
+                    0 => 12,
+                    hours => hours,
+                };
+                format!("{}:{:0>2} {}", hours, time.mins(), suffix)
+            }
+        }
+    }
+}
+
+/// Порядок записей в планере и в списке
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    /// По дате, времени и приоритету
+    #[default]
+    Time,
+    /// В порядке добавления, как записи лежат в файле
+    Insertion,
+    /// От недавно добавленных к старым
+    Created,
+}
+
+impl SortMode {
+    /// Получение порядка по названию из настроек (`time` или `insertion`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "time" => Some(Self::Time),
+            "insertion" => Some(Self::Insertion),
+            "created" => Some(Self::Created),
+            _ => None,
+        }
+    }
+}
+
+/// Формат файла хранилища
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageFormat {
+    Text,
+    Json,
+    Markdown,
+    /// Одна запись JSON на строку, новые записи дописываются в конец файла
+    JsonLines,
+    /// Список YAML, каждая запись - словарь с полями как в JSON
+    Yaml,
+}
+
+/// Определение формата хранилища по расширению файла
+impl StorageFormat {
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("md") => Self::Markdown,
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") => Self::JsonLines,
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Self::Yaml
+            }
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Хранилище записей
+pub struct Storage {
+    path: String,
+    format: StorageFormat,
+    max_entries: Option<usize>,
+    dry_run: bool,
+    keep_backups: usize,
+    quiet: bool,
+    strict: bool,
+    unique: bool,
+    write_retries: u32,
+    text_mirror: bool,
+    create_dirs: bool,
+    /// Можно ли задавать вопросы, по умолчанию - если ввод из терминала
+    interactive: bool,
+    /// Время изменения файла и записи при последнем чтении или записи,
+    /// по ним замечаются изменения из другой копии программы
+    seen: Mutex<Option<(SystemTime, Vec<Entry>)>>,
+    /// Записи, которые видел пользователь, если файл изменили извне
+    stale: Mutex<Option<Vec<Entry>>>,
+}
+
+impl Storage {
+    /// Создание хранилища с указанием пути к файлу хранилища,
+    /// формат определяется по расширению файла
+    pub fn new(path: &str) -> Self {
+        Self::with_format(path, StorageFormat::from_path(path))
+    }
+
+    /// Создание хранилища с явным указанием формата
+    pub fn with_format(path: &str, format: StorageFormat) -> Self {
+        Self {
+            path: path.to_string(),
+            format,
+            max_entries: None,
+            dry_run: false,
+            keep_backups: DEFAULT_BACKUPS,
+            quiet: false,
+            strict: false,
+            unique: false,
+            write_retries: DEFAULT_WRITE_RETRIES,
+            text_mirror: false,
+            create_dirs: true,
+            interactive: stdin().is_terminal(),
+            seen: Mutex::new(None),
+            stale: Mutex::new(None),
+        }
+    }
+
+    /// Строгое чтение: некорректные записи в файле - ошибка, а не пропуск
+    pub fn with_strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// Добавление без повторов: запись, совпадающая с уже сохраненной,
+    /// пропускается
+    pub fn with_unique(self, unique: bool) -> Self {
+        Self { unique, ..self }
+    }
+
+    /// Тихий режим: подтверждения изменений не выводятся
+    pub fn with_quiet(self, quiet: bool) -> Self {
+        Self { quiet, ..self }
+    }
+
+    /// Число повторов записи файла при временной ошибке, 0 отключает повторы
+    pub fn with_write_retries(self, write_retries: u32) -> Self {
+        Self {
+            write_retries,
+            ..self
+        }
+    }
+
+    /// Вопросы пользователю, без них изменение файла извне объединяется
+    /// молча
+    pub fn with_interactive(self, interactive: bool) -> Self {
+        Self {
+            interactive,
+            ..self
+        }
+    }
+
+    /// Создание недостающего каталога файла при первой записи, без него
+    /// отсутствующий каталог - ошибка
+    pub fn with_create_dirs(self, create_dirs: bool) -> Self {
+        Self {
+            create_dirs,
+            ..self
+        }
+    }
+
+    /// Текстовая копия при каждом сохранении планера в формате JSON
+    pub fn with_text_mirror(self, text_mirror: bool) -> Self {
+        Self {
+            text_mirror,
+            ..self
+        }
+    }
+
+    /// Число хранимых пронумерованных резервных копий, 0 отключает их
+    pub fn with_backups(self, keep_backups: usize) -> Self {
+        Self {
+            keep_backups,
+            ..self
+        }
+    }
+
+    /// Пробный запуск: изменения показываются, но не записываются в файл
+    pub fn with_dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
+    /// Ограничение числа записей в планере, без ограничения по умолчанию
+    pub fn with_max_entries(self, max_entries: Option<usize>) -> Self {
+        Self {
+            max_entries,
+            ..self
+        }
+    }
+
+    /// Проверка, что после добавления записей их число не превысит лимит
+    fn check_limit(&self, count: usize) -> Result<()> {
+        if self.max_entries.is_some_and(|max| count > max) {
+            Err(AppError::Msg("Достигнут лимит записей"))?
+        }
+
+        Ok(())
+    }
+
+    /// Записи планера перед добавлением новых. Дописывание в JSON Lines не
+    /// читает файл целиком, если этого не требуют проверка повторов, лимит
+    /// записей или внешнее изменение файла, поэтому предупреждение о занятом
+    /// времени при этом не выводится
+    fn read_before_append(&self) -> Result<Vec<Box<dyn EntryTrait>>> {
+        if self.format == StorageFormat::JsonLines
+            && !self.dry_run
+            && !self.unique
+            && self.max_entries.is_none()
+            && self.stale.lock().unwrap().is_none()
+        {
+            return Ok(Vec::new());
+        }
+
+        self.read()
+    }
+
+    /// Добавление и сохранение отсортированных записей планера в файл
+    pub fn save(&self, entry: Box<dyn EntryTrait>) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read_before_append()?;
+        if self.unique && list.contains(&entry) {
+            println!("{}", tr("Уже существует, пропущено"));
+            return Ok(());
+        }
+        self.check_limit(list.len() + 1)?;
+        if Self::conflicts(&list, entry.as_ref()) {
+            println!("{}", tr("Внимание: на это время уже есть задача"));
+        }
+
+        if self.format == StorageFormat::JsonLines && !self.dry_run {
+            self.append(&[entry])?;
+            self.report("Сохранено");
+            return Ok(());
+        }
+
+        list.push(entry);
+        sort_entries(&mut list);
+
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(())
+    }
+
+    /// Сохранение нескольких записей планера одной записью файла
+    pub fn save_all(&self, mut entries: Vec<Box<dyn EntryTrait>>) -> Result<usize> {
+        let _lock = self.lock()?;
+        let mut list = self.read_before_append()?;
+        if self.unique {
+            let mut kept: Vec<Box<dyn EntryTrait>> = Vec::new();
+            for entry in entries {
+                if list.contains(&entry) || kept.contains(&entry) {
+                    println!("{}: {}", tr("Уже существует, пропущено"), entry.target());
+                } else {
+                    kept.push(entry);
+                }
+            }
+            entries = kept;
+        }
+        let count = entries.len();
+        self.check_limit(list.len() + count)?;
+
+        if self.format == StorageFormat::JsonLines && !self.dry_run {
+            self.append(&entries)?;
+            self.report("Сохранено");
+            return Ok(count);
+        }
+
+        list.extend(entries);
+        sort_entries(&mut list);
+
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(count)
+    }
+
+    /// Удаление записи планера по индексу
+    pub fn delete(&self, index: usize) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let index = self.remap(index, &list)?;
+        if index >= list.len() {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        }
+
+        list.remove(index);
+        sort_entries(&mut list);
+
+        self.write(&list)?;
+
+        self.report("Удалено");
+
+        Ok(())
+    }
+
+    /// Поиск записей, задача которых содержит строку без учета регистра
+    pub fn search(&self, query: &str) -> Result<Vec<Box<dyn EntryTrait>>> {
+        let query = query.to_lowercase();
+        let mut list = self.read()?;
+        list.retain(|entry| entry.target().to_lowercase().contains(&query));
+
+        Ok(list)
+    }
+
+    /// Записи, которые начинаются в промежутке от `from` до `to`
+    /// включительно, задачи без времени в промежуток не попадают
+    pub fn in_range(&self, from: Time, to: Time) -> Result<Vec<Box<dyn EntryTrait>>> {
+        let mut list = self.read()?;
+        list.retain(|entry| entry.time().is_some_and(|time| from <= time && time <= to));
+
+        Ok(list)
+    }
+
+    /// Номера записей, подходящих под запрос: время, например "9:30",
+    /// или часть текста задачи без учета регистра
+    pub fn find(&self, query: &str) -> Result<Vec<usize>> {
+        Ok(Self::matches(&self.read()?, query))
+    }
+
+    /// Поиск записей списка по времени или части текста задачи
+    pub fn matches(list: &[Box<dyn EntryTrait>], query: &str) -> Vec<usize> {
+        let indices = |pred: &dyn Fn(&dyn EntryTrait) -> bool| {
+            list.iter()
+                .enumerate()
+                .filter(|(_, entry)| pred(entry.as_ref()))
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>()
+        };
+
+        if let Ok(time) = query.parse::<Time>() {
+            let found = indices(&|entry| entry.time() == Some(time));
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        let query = query.trim().to_lowercase();
+        indices(&|entry| entry.target().to_lowercase().contains(&query))
+    }
+
+    /// Последние `limit` разных задач, от недавно добавленных к старым.
+    /// Задачи сравниваются без учета регистра и лишних пробелов, а записи
+    /// без времени добавления считаются старше остальных и идут в порядке,
+    /// обратном порядку файла
+    pub fn recent_targets(&self, limit: usize) -> Result<Vec<String>> {
+        let mut list = self.read()?;
+        list.reverse();
+        list.sort_by(|a, b| newest_first(a.as_ref(), b.as_ref()));
+
+        let mut seen = HashSet::new();
+        let targets = list
+            .iter()
+            .filter(|entry| seen.insert(entry.target_key()))
+            .map(|entry| entry.target().to_owned())
+            .take(limit)
+            .collect();
+
+        Ok(targets)
+    }
+
+    /// Поиск записи по ID
+    pub fn find_by_id(&self, id: u32) -> Result<Option<Box<dyn EntryTrait>>> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .find(|entry| entry.id() == Some(id)))
+    }
+
+    /// Индекс записи с ID в текущем порядке записей
+    pub fn index_of(&self, id: u32) -> Result<usize> {
+        let index = self
+            .read()?
+            .iter()
+            .position(|entry| entry.id() == Some(id))
+            .ok_or(AppError::Msg("Записи с таким ID нет."))?;
+
+        Ok(index)
+    }
+
+    /// Проверка, есть ли уже задача на то же время и день,
+    /// задачи без времени друг другу не мешают
+    pub fn has_conflict(&self, entry: &dyn EntryTrait) -> Result<bool> {
+        Ok(Self::conflicts(&self.read()?, entry))
+    }
+
+    fn conflicts(list: &[Box<dyn EntryTrait>], entry: &dyn EntryTrait) -> bool {
+        entry.time().is_some()
+            && list
+                .iter()
+                .any(|other| other.date() == entry.date() && other.time() == entry.time())
+    }
+
+    /// Обмен местами двух записей на одно и то же время. Записи этого
+    /// времени получают порядковые номера по текущему порядку, после чего
+    /// номера двух записей меняются
+    pub fn swap(&self, first: usize, second: usize) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let (first, second) = (self.remap(first, &list)?, self.remap(second, &list)?);
+        let (Some(a), Some(b)) = (list.get(first), list.get(second)) else {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        };
+        if a.date() != b.date() || a.time() != b.time() {
+            Err(AppError::Msg(
+                "Поменять местами можно только записи на одно время.",
+            ))?
+        }
+        if a.priority() != b.priority() {
+            Err(AppError::Msg(
+                "Записи с разным приоритетом упорядочены по приоритету.",
+            ))?
+        }
+
+        let (date, time) = (a.date().to_owned(), a.time());
+        let mut order = 0;
+        for entry in list.iter_mut() {
+            if entry.date() == date && entry.time() == time {
+                let mut numbered = Entry::from(entry.as_ref());
+                numbered.order = order;
+                *entry = numbered.into();
+                order += 1;
+            }
+        }
+
+        let (order_a, order_b) = (list[first].order(), list[second].order());
+        for (index, order) in [(first, order_b), (second, order_a)] {
+            let mut entry = Entry::from(list[index].as_ref());
+            entry.order = order;
+            list[index] = entry.into();
+        }
+        // Без сортировки по времени записи меняются местами в самом файле
+        match sort_mode() {
+            SortMode::Time => list.sort(),
+            SortMode::Insertion => list.swap(first, second),
+            SortMode::Created => sort_entries(&mut list),
+        }
+
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(())
+    }
+
+    /// Объединение двух записей: первая запись получает более раннее из
+    /// двух времен и задачу вида `первая + вторая`, а вторая удаляется.
+    /// Задача без времени считается позже любой задачи со временем
+    pub fn merge(&self, first: usize, second: usize) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let (first, second) = (self.remap(first, &list)?, self.remap(second, &list)?);
+        if first == second {
+            Err(AppError::Msg("Нельзя объединить запись саму с собой."))?
+        }
+        let (Some(a), Some(b)) = (list.get(first), list.get(second)) else {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        };
+
+        let start = |entry: &dyn EntryTrait| {
+            (
+                entry.date().to_owned(),
+                entry.time().is_none(),
+                entry.time().map(day_minutes),
+            )
+        };
+        let mut merged = Entry::from(a.as_ref());
+        if start(b.as_ref()) < start(a.as_ref()) {
+            merged.date = b.date().to_owned();
+            merged.time = b.time();
+            merged.offset = b.offset();
+        }
+        merged.target = format!("{} + {}", a.target(), b.target());
+        Entry::check_target(&merged.target, max_target_length())?;
+        if !b.notes().is_empty() {
+            merged.notes = [a.notes(), b.notes()]
+                .into_iter()
+                .filter(|notes| !notes.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        list[first] = merged.into();
+        list.remove(second);
+        sort_entries(&mut list);
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(())
+    }
+
+    /// Замена записи планера по индексу
+    pub fn update(&self, index: usize, entry: Entry) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let index = self.remap(index, &list)?;
+        if index >= list.len() {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        }
+
+        list[index] = entry.into();
+        sort_entries(&mut list);
+
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(())
+    }
+
+    /// Удаление всех записей планера
+    pub fn clear(&self) -> Result<()> {
+        let _lock = self.lock()?;
+        self.write(&[])?;
+
+        self.report("Удалено");
+
+        Ok(())
+    }
+
+    /// Отметка записи планера по индексу выполненной или невыполненной
+    pub fn set_done(&self, index: usize, done: bool) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let index = self.remap(index, &list)?;
+        if index >= list.len() {
+            Err(AppError::Msg("Записи с таким номером нет."))?
+        }
+
+        let mut entry = Entry::from(list[index].as_ref());
+        entry.done = done;
+        list[index] = entry.into();
+
+        self.write(&list)?;
+
+        self.report("Сохранено");
+
+        Ok(())
+    }
+
+    /// Совпадение всех полей записей, кроме ID. В отличие от равенства
+    /// записей различаются регистр задачи, отметка, категория и заметки
+    fn same_fields(a: &dyn EntryTrait, b: &dyn EntryTrait) -> bool {
+        let fields = |entry: &dyn EntryTrait| {
+            let mut value = serde_json::Value::from(entry);
+            if let Some(fields) = value.as_object_mut() {
+                fields.remove("id");
+            }
+            value
+        };
+
+        fields(a) == fields(b)
+    }
+
+    /// Импорт записей из другого файла планера, формат которого
+    /// определяется по расширению. Точные копии уже существующих записей
+    /// пропускаются, возвращается число добавленных записей
+    pub fn import(&self, other: &str) -> Result<usize> {
+        if !Path::new(other).is_file() {
+            Err(AppError::Msg("Файл для импорта не найден."))?
+        }
+        let imported = Storage::new(other).read()?;
+
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let mut count = 0;
+        for entry in imported {
+            // ID другого планера здесь ничего не значат, запись получит новый
+            if !list
+                .iter()
+                .any(|known| Self::same_fields(known.as_ref(), entry.as_ref()))
+            {
+                list.push(
+                    Entry {
+                        id: None,
+                        ..Entry::from(entry.as_ref())
+                    }
+                    .into(),
+                );
+                count += 1;
+            }
+        }
+
+        self.check_limit(list.len())?;
+        if count > 0 {
+            sort_entries(&mut list);
+            self.write(&list)?;
+        }
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok(count)
+    }
+
+    /// Добавление записей, время которых в тот же день еще не занято.
+    /// Возвращается число добавленных записей
+    pub fn add_missing(&self, entries: Vec<Box<dyn EntryTrait>>) -> Result<usize> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let mut count = 0;
+        for entry in entries {
+            if !Self::conflicts(&list, entry.as_ref()) {
+                list.push(entry);
+                count += 1;
+            }
+        }
+
+        self.check_limit(list.len())?;
+        if count > 0 {
+            sort_entries(&mut list);
+            self.write(&list)?;
+        }
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok(count)
+    }
+
+    /// Переименование категории во всех записях планера, `ignore_case`
+    /// сравнивает старое название без учета регистра. Возвращается число
+    /// измененных записей
+    pub fn rename_category(&self, old: &str, new: &str, ignore_case: bool) -> Result<usize> {
+        Entry::check_category(new)?;
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let mut count = 0;
+        for entry in list.iter_mut() {
+            let matches = match ignore_case {
+                true => entry.category().to_lowercase() == old.to_lowercase(),
+                false => entry.category() == old,
+            };
+            if matches && entry.category() != new {
+                let mut renamed = Entry::from(entry.as_ref());
+                renamed.category = new.to_owned();
+                *entry = renamed.into();
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.write(&list)?;
+        }
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok(count)
+    }
+
+    /// Перенос невыполненных записей с прошедших дней на `today`, возвращается
+    /// число перенесенных записей. Выполненные, повторяющиеся и записи без
+    /// даты не переносятся
+    pub fn rollover(&self, today: NaiveDate) -> Result<usize> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let mut count = 0;
+        for entry in list.iter_mut() {
+            let overdue =
+                NaiveDate::parse_from_str(entry.date(), DATE_FORMAT).is_ok_and(|date| date < today);
+            if overdue && !entry.done() && entry.recurrence().is_none() {
+                let mut moved = Entry::from(entry.as_ref());
+                moved.date = today.format(DATE_FORMAT).to_string();
+                *entry = moved.into();
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            sort_entries(&mut list);
+            self.write(&list)?;
+        }
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok(count)
+    }
+
+    /// Сдвиг времени записей на `delta` минут, `range` ограничивает записи
+    /// по времени начала. Записи без времени не меняются, а записи, время
+    /// которых вышло бы за 0:00 или 23:59, пропускаются. Возвращается число
+    /// сдвинутых записей и задачи пропущенных
+    pub fn shift(&self, delta: i32, range: Option<(Time, Time)>) -> Result<(usize, Vec<String>)> {
+        let _lock = self.lock()?;
+        let mut list = self.read()?;
+        let mut count = 0;
+        let mut skipped = Vec::new();
+        for entry in list.iter_mut() {
+            let Some(time) = entry.time() else {
+                continue;
+            };
+            if range.is_some_and(|(from, to)| time < from || to < time) {
+                continue;
+            }
+
+            let minutes = i64::from(time.minutes()) + i64::from(delta);
+            match u32::try_from(minutes) {
+                Ok(minutes) if minutes <= Time::MAX.minutes() => {
+                    let mut shifted = Entry::from(entry.as_ref());
+                    shifted.time = Some(Time::from_minutes(minutes));
+                    *entry = shifted.into();
+                    count += 1;
+                }
+                _ => skipped.push(entry.target().to_owned()),
+            }
+        }
+
+        if count > 0 {
+            sort_entries(&mut list);
+            self.write(&list)?;
+        }
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok((count, skipped))
+    }
+
+    /// Путь к файлу хранилища
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Путь к архиву по умолчанию рядом с файлом планера:
+    /// `my-planner.txt` архивируется в `my-planner.archive.txt`
+    pub fn default_archive_path(&self) -> String {
+        let path = Path::new(&self.path);
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("my-planner");
+        let name = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => format!("{}.archive.{}", stem, ext),
+            None => format!("{}.archive", stem),
+        };
+
+        path.with_file_name(name).to_string_lossy().into_owned()
+    }
+
+    /// Перенос выполненных записей в конец архива `archive`, возвращается
+    /// число перенесенных записей. Архив пишется первым, чтобы сбой
+    /// не потерял записи
+    pub fn archive(&self, archive: &Storage) -> Result<usize> {
+        let _lock = self.lock()?;
+        let (done, rest): (Vec<_>, Vec<_>) =
+            self.read()?.into_iter().partition(|entry| entry.done());
+        if done.is_empty() {
+            return Ok(0);
+        }
+
+        {
+            let _archive_lock = archive.lock()?;
+            let mut archived = archive.read()?;
+            archived.extend(done.iter().map(|entry| Entry::from(entry.as_ref()).into()));
+            archive.write(&archived)?;
+        }
+        self.write(&rest)?;
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        }
+
+        Ok(done.len())
+    }
+
+    /// Экспорт записей планера в CSV файл
+    pub fn export_csv(&self, path: &str) -> Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(b"date,time,target,category,priority,duration_minutes,done\n")?;
+
+        for entry in self.read()? {
+            let row = [
+                entry.date().to_owned(),
+                entry
+                    .time()
+                    .map(|time| time.to_string())
+                    .unwrap_or_default(),
+                entry.target().to_owned(),
+                entry.category().to_owned(),
+                entry.priority().to_string(),
+                entry
+                    .duration_minutes()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                entry.done().to_string(),
+            ];
+            let row = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+            file.write_fmt(format_args!("{}\n", row.join(",")))?;
+        }
+
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Экспорт записей планера в календарь iCalendar (.ics)
+    ///
+    /// Записи без даты выгружаются на сегодня, повторяющиеся записи
+    /// получают правило RRULE, а задачи без времени - события на весь день.
+    /// Строки разделяются CRLF и переносятся после 75 байт, как требует RFC 5545
+    pub fn export_ics(&self, path: &str) -> Result<()> {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "VERSION:2.0".to_owned(),
+            "PRODID:-//my-planner//RU".to_owned(),
+        ];
+        let stamp = Local::now().format("%Y%m%dT%H%M%S");
+
+        for entry in self.read()? {
+            let date = match entry.date() {
+                "" => today(),
+                date => NaiveDate::parse_from_str(date, DATE_FORMAT)?,
+            };
+
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!("UID:{}", ics_uid(entry.as_ref())));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            match entry.time() {
+                Some(time) => {
+                    let start = starts_at(date, time);
+                    lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                    if let Some(duration) = entry.duration_minutes() {
+                        let end = start + chrono::Duration::minutes(duration.into());
+                        lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+                    }
+                }
+                None => lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d"))),
+            }
+            match entry.recurrence() {
+                Some(Recurrence::Daily) => lines.push("RRULE:FREQ=DAILY".to_owned()),
+                Some(Recurrence::Weekly(day)) => lines.push(format!(
+                    "RRULE:FREQ=WEEKLY;BYDAY={}",
+                    day.to_string()[..2].to_uppercase()
+                )),
+                None => {}
+            }
+            lines.push(format!("SUMMARY:{}", ics_text(entry.target())));
+            lines.push(format!("CATEGORIES:{}", ics_text(entry.category())));
+            if !entry.notes().is_empty() {
+                lines.push(format!("DESCRIPTION:{}", ics_text(entry.notes())));
+            }
+            if let Some(reminder) = entry.reminder_minutes() {
+                lines.push("BEGIN:VALARM".to_owned());
+                lines.push("ACTION:DISPLAY".to_owned());
+                lines.push(format!("DESCRIPTION:{}", ics_text(entry.target())));
+                lines.push(format!("TRIGGER:-PT{}M", reminder));
+                lines.push("END:VALARM".to_owned());
+            }
+            lines.push("END:VEVENT".to_owned());
+        }
+        lines.push("END:VCALENDAR".to_owned());
+
+        let mut file = File::create(path)?;
+        for line in lines {
+            file.write_fmt(format_args!("{}\r\n", ics_fold(&line)))?;
+        }
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Получение списка записей планера из файла
+    pub fn read(&self) -> Result<Vec<Box<dyn EntryTrait>>> {
+        self.check_dir()?;
+
+        // Каталог еще не создан: планер пуст, а каталог и файл
+        // появятся при первой записи
+        if !self.dir_exists() {
+            self.remember(&[]);
+            return Ok(Vec::new());
+        }
+        if !Path::new(&self.path).exists() {
+            if !self.dry_run {
+                write(&self.path, "")?;
+            }
+            self.remember(&[]);
+            return Ok(Vec::new());
+        }
+
+        // Текстовый формат разбирается построчно по мере чтения файла,
+        // JSON документ разбирается целиком
+        let mut skipped = Vec::new();
+        let list = match self.format {
+            StorageFormat::Text => {
+                Self::parse_text(BufReader::new(File::open(&self.path)?), &mut skipped)?
+            }
+            StorageFormat::Json => Self::parse_json(&read_to_string(&self.path)?)?,
+            StorageFormat::Markdown => {
+                Self::parse_markdown(BufReader::new(File::open(&self.path)?))?
+            }
+            StorageFormat::JsonLines => {
+                Self::parse_json_lines(BufReader::new(File::open(&self.path)?), &mut skipped)?
+            }
+            StorageFormat::Yaml => {
+                Self::parse_yaml(BufReader::new(File::open(&self.path)?), &mut skipped)?
+            }
+        };
+
+        // Неразобранные записи не теряются молча: о них предупреждается,
+        // а со --strict чтение завершается ошибкой
+        if !skipped.is_empty() {
+            let lines = skipped
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            SKIPPED_WARNING.call_once(|| {
+                eprintln!(
+                    "{}",
+                    tr("Пропущено некорректных записей: {} (строки {})")
+                        .replacen("{}", &skipped.len().to_string(), 1)
+                        .replacen("{}", &lines, 1)
+                );
+            });
+            if self.strict {
+                Err(AppError::Msg("Файл планера содержит некорректные записи."))?
+            }
+        }
+        log_debug!("прочитано записей из {}: {}", self.path, list.len());
+        self.remember(&list);
+
+        Ok(list)
+    }
+
+    /// Запись списка записей планера в файл
+    ///
+    /// Данные сначала пишутся во временный файл, который затем атомарно
+    /// переименовывается поверх файла хранилища, поэтому прерванная запись
+    /// не портит планер
+    fn write(&self, list: &[Box<dyn EntryTrait>]) -> Result<()> {
+        self.check_dir()?;
+
+        // Пробный запуск только показывает, каким стал бы планер
+        if self.dry_run {
+            let list = list
+                .iter()
+                .map(|entry| Entry::from(entry.as_ref()).into())
+                .collect();
+            print!("{}", ListView::default().render(list));
+            return Ok(());
+        }
+
+        self.prepare_dir()?;
+        let list = &Self::with_ids(&[], list);
+        let tmp_path = format!("{}.tmp", self.path);
+        let buf = Self::encode(self.format, list)?;
+
+        // Временный файл каждый раз пишется заново, поэтому запись
+        // можно безопасно повторить
+        self.retry(|| {
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(&buf)?;
+            tmp.sync_all()
+        })?;
+
+        self.backup()?;
+        self.retry(|| rename(&tmp_path, &self.path))?;
+        log_debug!("записано записей в {}: {}", self.path, list.len());
+        self.remember(list);
+        self.update_mirror(|| self.mirror(list));
+
+        Ok(())
+    }
+
+    /// Содержимое файла хранилища в указанном формате
+    fn encode(format: StorageFormat, list: &[Box<dyn EntryTrait>]) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+
+        match format {
+            StorageFormat::Text => {
+                for entry in list {
+                    buf.write_fmt(format_args!(
+                        "{}\n{}\n",
+                        entry
+                            .time()
+                            .map_or(NO_TIME.to_owned(), |time| time.to_string()),
+                        entry.target()
+                    ))?;
+                    for (key, value) in Self::fields(entry.as_ref()) {
+                        buf.write_fmt(format_args!("{}: {}\n", key, value))?;
+                    }
+                    buf.write_all(b"\n")?;
+                }
+            }
+            StorageFormat::Markdown => {
+                for entry in list {
+                    // Задача без времени записывается пунктом без времени
+                    let item = match entry.time() {
+                        Some(time) => format!("{} — {}", time, entry.target()),
+                        None => entry.target().to_owned(),
+                    };
+                    buf.write_fmt(format_args!(
+                        "- [{}] {}\n",
+                        if entry.done() { 'x' } else { ' ' },
+                        item
+                    ))?;
+                    for (key, value) in Self::fields(entry.as_ref()) {
+                        if key != "done" {
+                            buf.write_fmt(format_args!("  - {}: {}\n", key, value))?;
+                        }
+                    }
+                }
+            }
+            StorageFormat::Json => {
+                let list = list
+                    .iter()
+                    .map(|entry| entry.as_ref().into())
+                    .collect::<Vec<serde_json::Value>>();
+                buf.write_all(serde_json::to_string_pretty(&list)?.as_bytes())?;
+            }
+            StorageFormat::JsonLines => {
+                for entry in list {
+                    let value: serde_json::Value = entry.as_ref().into();
+                    buf.write_fmt(format_args!("{}\n", value))?;
+                }
+            }
+            // Поля берутся из JSON представления записи, пустые поля
+            // пропускаются, а строки записываются в двойных кавычках
+            StorageFormat::Yaml => {
+                if list.is_empty() {
+                    buf.write_all(b"[]\n")?;
+                }
+                for entry in list {
+                    let value: serde_json::Value = entry.as_ref().into();
+                    let fields = value.as_object().into_iter().flatten();
+                    let mut prefix = "- ";
+                    for (key, value) in fields.filter(|(_, value)| !value.is_null()) {
+                        let value = match value {
+                            serde_json::Value::String(value) => serde_json::to_string(value)?,
+                            value => value.to_string(),
+                        };
+                        buf.write_fmt(format_args!("{}{}: {}\n", prefix, key, value))?;
+                        prefix = "  ";
+                    }
+                }
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Текстовая копия планера рядом с файлом JSON: `planner.json`
+    /// дополняется `planner.txt` в обычном текстовом формате
+    fn mirror_path(&self) -> Option<PathBuf> {
+        if !self.text_mirror
+            || !matches!(self.format, StorageFormat::Json | StorageFormat::JsonLines)
+        {
+            return None;
+        }
+        Some(Path::new(&self.path).with_extension("txt"))
+    }
+
+    /// Запись текстовой копии, копия только пишется и никогда не читается
+    fn mirror(&self, list: &[Box<dyn EntryTrait>]) -> Result<()> {
+        let Some(path) = self.mirror_path() else {
+            return Ok(());
+        };
+        std::fs::write(&path, Self::encode(StorageFormat::Text, list)?)?;
+        log_debug!("записана текстовая копия {}", path.display());
+
+        Ok(())
+    }
+
+    /// Ошибка записи текстовой копии не отменяет уже сохраненные
+    /// изменения и выводится предупреждением
+    fn update_mirror(&self, update: impl FnOnce() -> Result<()>) {
+        if self.mirror_path().is_none() {
+            return;
+        }
+        if let Err(e) = update() {
+            eprintln!(
+                "{}",
+                tr("Внимание: не удалось записать текстовую копию: {}")
+                    .replace("{}", &e.to_string())
+            );
+        }
+    }
+
+    /// Дописывание записей в конец файла формата JSON Lines без перезаписи
+    /// остальных, порядок файла при этом не сортируется. ID дописанным
+    /// записям назначаются при чтении
+    ///
+    /// Вместо копии файла для отмены запоминается его прежняя длина, а
+    /// пронумерованные копии не сдвигаются
+    fn append(&self, entries: &[Box<dyn EntryTrait>]) -> Result<()> {
+        self.prepare_dir()?;
+        let length = std::fs::metadata(&self.path).map_or(0, |meta| meta.len());
+        write(self.undo_path(), length.to_string())?;
+        if Path::new(&self.backup_path()).exists() {
+            remove_file(self.backup_path())?;
+        }
+
+        // Повторяется только открытие: повтор дописывания мог бы
+        // продублировать уже дописанные строки
+        let mut file = self.retry(|| File::options().create(true).append(true).open(&self.path))?;
+        for entry in entries {
+            let value: serde_json::Value = entry.as_ref().into();
+            file.write_fmt(format_args!("{}\n", value))?;
+        }
+        file.flush()?;
+        file.sync_all()?;
+        log_debug!("дописано записей в {}: {}", self.path, entries.len());
+
+        // Дописанные записи - свое изменение, а не внешнее
+        let modified = self.modified();
+        if let Some((time, list)) = self.seen.lock().unwrap().as_mut() {
+            *time = modified.unwrap_or(*time);
+            list.extend(entries.iter().map(|entry| Entry::from(entry.as_ref())));
+        }
+        // Копии нужен весь планер, а не только дописанные записи
+        self.update_mirror(|| self.mirror(&self.read()?));
+
+        Ok(())
+    }
+
+    /// Дополнительные поля записи в виде пар `ключ: значение`, поля со
+    /// значением по умолчанию пропускаются
+    fn fields(entry: &dyn EntryTrait) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if !entry.date().is_empty() {
+            fields.push(("date", entry.date().to_owned()));
+        }
+        fields.push(("priority", entry.priority().to_string()));
+        if let Some(duration) = entry.duration_minutes() {
+            fields.push(("duration", duration.to_string()));
+        }
+        if entry.done() {
+            fields.push(("done", "true".to_owned()));
+        }
+        fields.push(("category", entry.category().to_owned()));
+        if let Some(recurrence) = entry.recurrence() {
+            fields.push(("recurrence", recurrence.to_string()));
+        }
+        if !entry.notes().is_empty() {
+            fields.push(("notes", escape(entry.notes())));
+        }
+        if let Some(reminder) = entry.reminder_minutes() {
+            fields.push(("reminder", reminder.to_string()));
+        }
+        if let Some(offset) = entry.offset() {
+            fields.push(("offset", offset.to_string()));
+        }
+        if entry.order() != 0 {
+            fields.push(("order", entry.order().to_string()));
+        }
+        if let Some(created_at) = entry.created_at() {
+            fields.push(("created", created_at.to_string()));
+        }
+        if let Some(id) = entry.id() {
+            fields.push(("id", id.to_string()));
+        }
+        if let Some(label) = entry.label() {
+            fields.push(("label", label.to_owned()));
+        }
+
+        fields
+    }
+
+    /// Копии записей, где записи без ID получают следующие номера после
+    /// наибольшего ID среди `known` и самих записей. ID не меняется при
+    /// сортировке и удалении других записей
+    fn with_ids(
+        known: &[Box<dyn EntryTrait>],
+        list: &[Box<dyn EntryTrait>],
+    ) -> Vec<Box<dyn EntryTrait>> {
+        let mut next = known
+            .iter()
+            .chain(list)
+            .filter_map(|entry| entry.id())
+            .max()
+            .unwrap_or(0);
+
+        list.iter()
+            .map(|entry| {
+                let mut entry = Entry::from(entry.as_ref());
+                if entry.id.is_none() {
+                    next += 1;
+                    entry.id = Some(next);
+                }
+                entry.into()
+            })
+            .collect()
+    }
+
+    /// Путь к резервной копии предыдущего состояния для отмены
+    fn backup_path(&self) -> String {
+        format!("{}.bak", self.path)
+    }
+
+    /// Путь к прежней длине файла JSON Lines для отмены дописывания
+    fn undo_path(&self) -> String {
+        format!("{}.undo", self.path)
+    }
+
+    /// Сохранение текущего содержимого файла в резервную копию перед
+    /// изменением, копия тоже пишется через временный файл
+    fn backup(&self) -> Result<()> {
+        self.rotate()?;
+        if Path::new(&self.undo_path()).exists() {
+            remove_file(self.undo_path())?;
+        }
+
+        let tmp_path = format!("{}.tmp", self.backup_path());
+        if Path::new(&self.path).exists() {
+            copy(&self.path, &tmp_path)?;
+        } else {
+            write(&tmp_path, "")?;
+        }
+        rename(&tmp_path, self.backup_path())?;
+
+        Ok(())
+    }
+
+    /// Путь к пронумерованной резервной копии, 1 - самая новая
+    fn rotated_path(&self, number: usize) -> String {
+        format!("{}.{}", self.path, number)
+    }
+
+    /// Сдвиг пронумерованных копий на один номер и сохранение текущего
+    /// файла копией 1, самая старая копия удаляется
+    fn rotate(&self) -> Result<()> {
+        if self.keep_backups == 0 || !Path::new(&self.path).exists() {
+            return Ok(());
+        }
+
+        for number in (1..self.keep_backups).rev() {
+            let path = self.rotated_path(number);
+            if Path::new(&path).exists() {
+                rename(&path, self.rotated_path(number + 1))?;
+            }
+        }
+        copy(&self.path, self.rotated_path(1))?;
+        log_debug!("резервные копии сдвинуты: {}", self.rotated_path(1));
+
+        Ok(())
+    }
+
+    /// Номера и время изменения существующих пронумерованных копий
+    pub fn backups(&self) -> Vec<(usize, DateTime<Local>)> {
+        (1..=self.keep_backups)
+            .filter_map(|number| {
+                let modified = std::fs::metadata(self.rotated_path(number))
+                    .and_then(|meta| meta.modified())
+                    .ok()?;
+                Some((number, modified.into()))
+            })
+            .collect()
+    }
+
+    /// Восстановление планера из пронумерованной копии, текущее состояние
+    /// при этом само попадает в копии и в `undo`
+    pub fn restore(&self, number: usize) -> Result<()> {
+        let _lock = self.lock()?;
+        let path = self.rotated_path(number);
+        if !Path::new(&path).is_file() {
+            Err(AppError::Msg("Копии с таким номером нет."))?
+        }
+
+        let list = Storage::with_format(&path, self.format).read()?;
+        self.write(&list)?;
+
+        self.report("Копия восстановлена");
+
+        Ok(())
+    }
+
+    /// Сообщение о выполненном изменении, при пробном запуске вместо него
+    /// сообщается, что изменения не сохранены
+    fn report(&self, msg: &'static str) {
+        if self.dry_run {
+            println!("{}", tr("(пробный запуск, изменения не сохранены)"));
+        } else if !self.quiet {
+            println!("{}", tr(msg));
+        }
+        if !self.quiet {
+            println!("====================================");
+        }
+    }
+
+    /// Отмена последнего изменения восстановлением резервной копии,
+    /// хранится только одно предыдущее состояние. Дописывание в JSON Lines
+    /// отменяется обрезкой файла до прежней длины
+    pub fn undo(&self) -> Result<()> {
+        let _lock = self.lock()?;
+        if Path::new(&self.undo_path()).exists() {
+            let length = read_to_string(self.undo_path())?
+                .trim()
+                .parse()
+                .map_err(|_| AppError::Msg("Нечего отменять."))?;
+            if !self.dry_run {
+                File::options()
+                    .write(true)
+                    .open(&self.path)?
+                    .set_len(length)?;
+                remove_file(self.undo_path())?;
+                *self.seen.lock().unwrap() = None;
+            }
+
+            self.report("Последнее изменение отменено");
+            return Ok(());
+        }
+        if !Path::new(&self.backup_path()).exists() {
+            Err(AppError::Msg("Нечего отменять."))?
+        }
+
+        if !self.dry_run {
+            rename(self.backup_path(), &self.path)?;
+            *self.seen.lock().unwrap() = None;
+        }
+
+        self.report("Последнее изменение отменено");
+
+        Ok(())
+    }
+
+    /// Захват блокировки планера на время изменения
+    ///
+    /// Сам файл планера заменяется переименованием, поэтому блокируется
+    /// отдельный файл рядом с ним. Занятая блокировка ожидается недолго,
+    /// после чего возвращается ошибка. Блокировка снимается при закрытии файла.
+    /// Пробный запуск ничего не меняет, поэтому и файл блокировки не создает,
+    /// а каталог создается только записью, так что до его появления
+    /// блокировать нечего
+    fn lock(&self) -> Result<Option<File>> {
+        self.check_dir()?;
+        if self.dry_run || !self.dir_exists() {
+            self.check_outside_changes()?;
+            return Ok(None);
+        }
+
+        let file = File::create(format!("{}.lock", self.path))?;
+
+        for _ in 0..LOCK_ATTEMPTS {
+            match file.try_lock() {
+                Ok(()) => {
+                    log_debug!("блокировка получена: {}.lock", self.path);
+                    self.check_outside_changes()?;
+                    return Ok(Some(file));
+                }
+                Err(TryLockError::WouldBlock) => {
+                    log_debug!("блокировка занята, ожидание: {}.lock", self.path);
+                    thread::sleep(LOCK_RETRY_DELAY)
+                }
+                Err(TryLockError::Error(err)) => Err(err)?,
+            }
+        }
+
+        Err(AppError::Msg("Планер занят другим процессом"))?
+    }
+
+    /// Выполнение операции с файлом, временные ошибки ввода-вывода
+    /// повторяются до `write_retries` раз с растущей паузой, остальные
+    /// ошибки возвращаются сразу
+    fn retry<T>(&self, mut op: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if attempt < self.write_retries && is_transient(&e) => {
+                    attempt += 1;
+                    log_debug!("ошибка записи {}, повтор {}: {}", self.path, attempt, e);
+                    thread::sleep(WRITE_RETRY_DELAY * attempt);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Время последнего изменения файла планера
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Запоминание состояния файла после чтения или записи
+    fn remember(&self, list: &[Box<dyn EntryTrait>]) {
+        *self.seen.lock().unwrap() = self.modified().map(|time| {
+            let list = list.iter().map(|entry| Entry::from(entry.as_ref()));
+            (time, list.collect())
+        });
+    }
+
+    /// Проверка перед изменением, что файл не менялся с последнего чтения.
+    /// Если его изменила другая копия программы, изменение применяется к
+    /// новому содержимому файла, а в терминале сначала спрашивается согласие
+    fn check_outside_changes(&self) -> Result<()> {
+        *self.stale.lock().unwrap() = None;
+        let Some((time, list)) = self.seen.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        if self.modified() == Some(time) {
+            return Ok(());
+        }
+
+        log_debug!("файл изменен извне: {}", self.path);
+        if self.interactive {
+            let answer = input(tr("Файл изменён извне, объединить? (да/нет): "))?;
+            if !matches!(answer.to_lowercase().as_str(), "да" | "yes") {
+                Err(AppError::Msg("Изменение отменено: файл изменён извне."))?
+            }
+        }
+        *self.stale.lock().unwrap() = Some(list);
+
+        Ok(())
+    }
+
+    /// Номер записи в свежем списке: если файл менялся извне, запись,
+    /// выбранная по старому списку, ищется среди новых записей
+    fn remap(&self, index: usize, list: &[Box<dyn EntryTrait>]) -> Result<usize> {
+        let stale = self.stale.lock().unwrap().clone();
+        let Some(entry) = stale.as_ref().and_then(|stale| stale.get(index)) else {
+            return Ok(index);
+        };
+        let entry: Box<dyn EntryTrait> = entry.clone().into();
+
+        let index = list
+            .iter()
+            .position(|other| other == &entry)
+            .ok_or(AppError::Msg("Запись изменена или удалена извне."))?;
+
+        Ok(index)
+    }
+
+    /// Проверка файла хранилища перед запуском команды: файл должен
+    /// читаться и разбираться без пропущенных записей, а ошибка сообщает
+    /// первую поврежденную строку. Отсутствующий файл - пустой планер
+    pub fn validate(&self) -> Result<()> {
+        if !Path::new(&self.path).exists() {
+            return Ok(());
+        }
+
+        let buf = read_to_string(&self.path)
+            .map_err(|_| AppError::Msg("Не удалось прочитать файл планера."))?;
+        let mut skipped = Vec::new();
+        match self.format {
+            StorageFormat::Text => {
+                Self::parse_text(buf.as_bytes(), &mut skipped)?;
+            }
+            StorageFormat::JsonLines => {
+                Self::parse_json_lines(buf.as_bytes(), &mut skipped)?;
+            }
+            StorageFormat::Markdown => {
+                Self::parse_markdown(buf.as_bytes())?;
+            }
+            StorageFormat::Yaml => {
+                Self::parse_yaml(buf.as_bytes(), &mut skipped)?;
+            }
+            // Ошибка разбора JSON сообщает строку, в которой она найдена
+            StorageFormat::Json => {
+                let parsed = serde_json::from_str::<serde_json::Value>(&buf);
+                if let (false, Err(e)) = (buf.trim().is_empty(), parsed) {
+                    Err(AppError::Corrupted(e.line()))?
+                }
+                Self::parse_json(&buf)?;
+            }
+        }
+        if let Some(line) = skipped.first() {
+            Err(AppError::Corrupted(*line))?
+        }
+
+        Ok(())
+    }
+
+    /// Проверка существования каталога с файлом хранилища
+    /// Отсутствующий каталог - ошибка, только если его не разрешено создавать
+    pub fn check_dir(&self) -> Result<()> {
+        if !self.create_dirs && !self.dir_exists() {
+            Err(AppError::Msg("Каталог для файла планера не существует."))?
+        }
+
+        Ok(())
+    }
+
+    /// Существует ли каталог файла хранилища, путь без каталога
+    /// относится к текущему каталогу
+    fn dir_exists(&self) -> bool {
+        match Path::new(&self.path).parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.is_dir(),
+            _ => true,
+        }
+    }
+
+    /// Создание недостающего каталога перед записью файла хранилища
+    fn prepare_dir(&self) -> Result<()> {
+        self.check_dir()?;
+        if let Some(dir) = Path::new(&self.path)
+            .parent()
+            .filter(|_| !self.dir_exists())
+        {
+            create_dir_all(dir)
+                .map_err(|_| AppError::Msg("Не удалось создать каталог для файла планера."))?;
+            log_debug!("создан каталог {}", dir.display());
+        }
+
+        Ok(())
+    }
+
+    /// Разбор текстового формата хранилища
+    ///
+    /// Записи разделены пустыми строками, поэтому лишние пустые строки
+    /// и окончания строк `\r\n` не ломают разбор
+    fn parse_text(
+        reader: impl BufRead,
+        skipped: &mut Vec<usize>,
+    ) -> Result<Vec<Box<dyn EntryTrait>>> {
+        let mut list = Vec::new();
+        let mut block = Vec::new();
+        let mut lines = reader.lines();
+        let (mut number, mut start) = (0, 1);
+        loop {
+            // Конец файла завершает последнюю запись так же, как пустая строка
+            let line = lines.next().transpose()?;
+            number += 1;
+            if let Some(line) = line.as_deref().map(str::trim) {
+                if !line.is_empty() {
+                    if block.is_empty() {
+                        start = number;
+                    }
+                    block.push(line.to_owned());
+                    continue;
+                }
+            }
+
+            match Self::parse_block(&block) {
+                Some(entry) => list.push(entry.into()),
+                None if !block.is_empty() => skipped.push(start),
+                None => {}
+            }
+            block.clear();
+
+            if line.is_none() {
+                break;
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Разбор одной записи текстового формата, запись с неверным
+    /// временем пропускается, а `-` вместо времени означает задачу без времени
+    fn parse_block(block: &[String]) -> Option<Entry> {
+        let [time, target, fields @ ..] = block else {
+            return None;
+        };
+
+        let mut entry = Entry {
+            time: match time.as_str() {
+                NO_TIME => None,
+                time => Some(time.parse().ok()?),
+            },
+            target: target.to_string(),
+            ..Default::default()
+        };
+
+        // Дополнительные поля записаны строками вида `ключ: значение`,
+        // в старых файлах их нет
+        for line in fields {
+            Self::parse_field(&mut entry, line);
+        }
+
+        Some(entry)
+    }
+
+    /// Разбор дополнительного поля записи вида `ключ: значение`,
+    /// неизвестные поля пропускаются
+    fn parse_field(entry: &mut Entry, line: &str) {
+        match line.split_once(": ") {
+            Some(("date", date)) => entry.date = date.to_owned(),
+            Some(("priority", priority)) => {
+                entry.priority = priority.parse().unwrap_or(DEFAULT_PRIORITY)
+            }
+            Some(("duration", duration)) => entry.duration_minutes = duration.parse().ok(),
+            Some(("done", done)) => entry.done = done == "true",
+            Some(("category", category)) => entry.category = category.to_owned(),
+            Some(("recurrence", recurrence)) => entry.recurrence = recurrence.parse().ok(),
+            Some(("notes", notes)) => entry.notes = unescape(notes),
+            Some(("reminder", reminder)) => entry.reminder_minutes = reminder.parse().ok(),
+            Some(("offset", offset)) => entry.offset = offset.parse().ok(),
+            Some(("order", order)) => entry.order = order.parse().unwrap_or_default(),
+            Some(("created", created_at)) => entry.created_at = created_at.parse().ok(),
+            Some(("id", id)) => entry.id = id.parse().ok(),
+            Some(("label", label)) => entry.label = Some(label.to_owned()),
+            _ => {}
+        }
+    }
+
+    /// Разбор Markdown формата хранилища
+    ///
+    /// Каждая запись - пункт списка с отметкой `- [ ] 9:30 — задача`,
+    /// дополнительные поля - вложенные пункты `  - ключ: значение`.
+    /// Пункт без времени в начале - задача без времени
+    fn parse_markdown(reader: impl BufRead) -> Result<Vec<Box<dyn EntryTrait>>> {
+        let mut list: Vec<Box<dyn EntryTrait>> = Vec::new();
+        let mut current: Option<Entry> = None;
+
+        for line in reader.lines() {
+            let line = line?;
+            let item = line.trim_end();
+
+            let checkbox = match (item.strip_prefix("- [ ] "), item.strip_prefix("- [x] ")) {
+                (Some(rest), _) => Some((rest, false)),
+                (_, Some(rest)) => Some((rest, true)),
+                _ => None,
+            };
+
+            if let Some((rest, done)) = checkbox {
+                list.extend(current.take().map(Into::into));
+                let (time, target) = match rest.split_once(" — ") {
+                    Some((time, target)) if time.parse::<Time>().is_ok() => {
+                        (time.parse().ok(), target)
+                    }
+                    _ => (None, rest),
+                };
+                current = Some(Entry {
+                    time,
+                    target: target.trim().to_owned(),
+                    done,
+                    ..Default::default()
+                });
+            } else if let (Some(entry), Some(field)) =
+                (current.as_mut(), item.trim_start().strip_prefix("- "))
+            {
+                if line.starts_with(char::is_whitespace) {
+                    Self::parse_field(entry, field);
+                }
+            }
+        }
+        list.extend(current.map(Into::into));
+
+        Ok(list)
+    }
+
+    /// Разбор формата JSON Lines, записи в файле хранятся в порядке
+    /// добавления и сортируются при чтении, а дописанные записи без ID
+    /// получают следующие номера в порядке файла. Номера неразобранных строк
+    /// собираются в `skipped`
+    fn parse_json_lines(
+        reader: impl BufRead,
+        skipped: &mut Vec<usize>,
+    ) -> Result<Vec<Box<dyn EntryTrait>>> {
+        let mut list: Vec<Box<dyn EntryTrait>> = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = serde_json::from_str::<serde_json::Value>(&line)
+                .map_err(|e| e.into())
+                .and_then(|value| Entry::try_from(&value));
+            match entry {
+                Ok(entry) => list.push(entry.into()),
+                Err(_) => skipped.push(i + 1),
+            }
+        }
+        let mut list = Self::with_ids(&[], &list);
+        sort_entries(&mut list);
+
+        Ok(list)
+    }
+
+    /// Разбор YAML формата хранилища
+    ///
+    /// Понимается то, что пишет сам планер, и простая ручная правка: список
+    /// словарей `- ключ: значение` без вложенных значений. Строки можно
+    /// записывать в кавычках и без них, а после значения без кавычек можно
+    /// оставить комментарий `# ...`. Запись, которую не удалось разобрать,
+    /// пропускается с номером ее первой строки
+    fn parse_yaml(
+        reader: impl BufRead,
+        skipped: &mut Vec<usize>,
+    ) -> Result<Vec<Box<dyn EntryTrait>>> {
+        // Начальная строка, поля и признак ошибки каждой записи
+        let mut records: Vec<(usize, serde_json::Map<String, serde_json::Value>, bool)> =
+            Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || matches!(trimmed, "---" | "[]") {
+                continue;
+            }
+
+            let field = if let Some(field) = line.strip_prefix("- ") {
+                records.push((i + 1, serde_json::Map::new(), false));
+                field
+            } else if line.starts_with(char::is_whitespace) && !records.is_empty() {
+                trimmed
+            } else {
+                skipped.push(i + 1);
+                continue;
+            };
+
+            let Some((_, fields, broken)) = records.last_mut() else {
+                continue;
+            };
+            match field.split_once(':').and_then(|(key, value)| {
+                let key = key.trim();
+                Some((key, yaml_value(key, value.trim())?))
+            }) {
+                Some((key, value)) => {
+                    fields.insert(key.to_owned(), value);
+                }
+                None => *broken = true,
+            }
+        }
+
+        let mut list: Vec<Box<dyn EntryTrait>> = Vec::new();
+        for (line, fields, broken) in records {
+            match Entry::try_from(&serde_json::Value::Object(fields)) {
+                Ok(entry) if !broken => list.push(entry.into()),
+                _ => skipped.push(line),
+            }
+        }
+        skipped.sort();
+        sort_entries(&mut list);
+
+        Ok(list)
+    }
+
+    /// Разбор JSON формата хранилища
+    fn parse_json(buf: &str) -> Result<Vec<Box<dyn EntryTrait>>> {
+        if buf.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let values: Vec<serde_json::Value> = serde_json::from_str(buf)?;
+        let mut list = Vec::new();
+        for value in &values {
+            list.push(Entry::try_from(value)?.into());
+        }
+
+        Ok(list)
+    }
+}
+
+/// Экранирование перевода строки и обратной косой черты, чтобы многострочное
+/// значение занимало одну строку текстового формата
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Значение поля YAML: строка в двойных кавычках разбирается как строка
+/// JSON, в одинарных - с удвоенной кавычкой внутри, а `~` и пустое значение
+/// означают отсутствие значения
+///
+/// Тип значения определяется полем, а не записью: `target: 42` и
+/// `target: yes` - строки, у `done` понимаются `yes`/`no` и `on`/`off`,
+/// а числовые поля можно записать и в кавычках
+fn yaml_value(key: &str, value: &str) -> Option<serde_json::Value> {
+    let text = match value {
+        "" | "~" | "null" | "Null" | "NULL" => return Some(serde_json::Value::Null),
+        value if value.starts_with('"') => serde_json::from_str(value).ok()?,
+        value if value.starts_with('\'') => {
+            let inner = value.strip_prefix('\'')?.strip_suffix('\'')?;
+            inner.replace("''", "'")
+        }
+        value => match value.split_once(" #") {
+            Some((value, _)) => value.trim_end().to_owned(),
+            None => value.to_owned(),
+        },
+    };
+
+    let value = match key {
+        "done" => match text.to_lowercase().as_str() {
+            "true" | "yes" | "on" => serde_json::Value::Bool(true),
+            "false" | "no" | "off" => serde_json::Value::Bool(false),
+            _ => return None,
+        },
+        "priority" | "duration_minutes" | "reminder_minutes" | "order" | "created_at" | "id" => {
+            text.parse::<u64>().ok()?.into()
+        }
+        _ => serde_json::Value::String(text),
+    };
+
+    Some(value)
+}
+
+/// Восстановление значения, экранированного функцией `escape`
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            (c, _) => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Экранирование поля CSV: поля с запятыми, кавычками и переводами строк
+/// заключаются в кавычки, а кавычки внутри удваиваются
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Экранирование текста iCalendar: обратная косая черта, точка с запятой,
+/// запятая и переводы строк
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Перенос строки iCalendar длиннее 75 байт: продолжение начинается
+/// с пробела, а многобайтовые символы не разрываются
+fn ics_fold(line: &str) -> String {
+    const LIMIT: usize = 75;
+
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LIMIT {
+            folded += "\r\n ";
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+/// Стабильный идентификатор события iCalendar, зависящий от даты,
+/// времени и текста задачи, чтобы повторный импорт обновлял события
+fn ics_uid(entry: &dyn EntryTrait) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (
+        entry.date(),
+        entry
+            .time()
+            .map(|time| time.to_string())
+            .unwrap_or_default()
+            .as_str(),
+        entry.target(),
+    )
+        .hash(&mut hasher);
+
+    format!("{:016x}@my-planner", hasher.finish())
+}
+
+/// Реализация сортировки записей
+///
+/// Записи на одно время с одинаковым приоритетом и порядком упорядочены
+/// по тексту задачи, а равенство записей совпадает с равенством при
+/// сравнении
+impl Ord for Box<dyn EntryTrait> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.date()
+            .cmp(other.date())
+            .then(self.time().is_none().cmp(&other.time().is_none()))
+            .then(
+                self.time()
+                    .map(day_minutes)
+                    .cmp(&other.time().map(day_minutes)),
+            )
+            .then(self.priority().cmp(&other.priority()))
+            .then(self.order().cmp(&other.order()))
+            .then_with(|| self.target_key().cmp(&other.target_key()))
+    }
+}
+
+impl Eq for Box<dyn EntryTrait> {}
+
+impl PartialEq for Box<dyn EntryTrait> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl PartialOrd for Box<dyn EntryTrait> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Путь к файлу во временном каталоге, свой для каждого теста, вместе
+    /// с оставшимися от прошлого запуска резервными копиями и блокировкой
+    fn temp_path(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("my_planner_test_{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join(name).to_string_lossy().into_owned();
+        for suffix in [
+            "", ".bak", ".undo", ".lock", ".tmp", ".1", ".2", ".3", ".4", ".5",
+        ] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        }
+
+        path
+    }
+
+    /// Хранилище без сообщений о сохранении
+    fn storage(path: &str) -> Storage {
+        Storage::new(path).with_quiet(true).with_interactive(false)
+    }
+
+    /// Запись на фиксированный день
+    fn entry(time: &str, target: &str) -> Entry {
+        Entry {
+            date: "2024-06-01".to_owned(),
+            time: Some(time.parse().unwrap()),
+            target: target.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Время и задача каждой записи списка
+    fn summary(list: &[Box<dyn EntryTrait>]) -> Vec<(Option<String>, String)> {
+        list.iter()
+            .map(|entry| {
+                (
+                    entry.time().map(|time| time.to_string()),
+                    entry.target().to_owned(),
+                )
+            })
+            .collect()
+    }
+
+    /// Момент времени в день записей из `entry`
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, 1)
+            .unwrap()
+            .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    /// Список записей для функций, принимающих записи планера
+    fn boxed(entries: &[Entry]) -> Vec<Box<dyn EntryTrait>> {
+        entries.iter().cloned().map(Into::into).collect()
+    }
+
+    #[test]
+    fn text_storage_round_trip_is_sorted_and_intact() {
+        let path = temp_path("round_trip.txt");
+        let storage = storage(&path);
+        for (time, target) in [("18:00", "ужин"), ("9:30", "зарядка"), ("12:15", "обед")]
+        {
+            storage.save(entry(time, target).into()).unwrap();
+        }
+
+        assert_eq!(
+            summary(&storage.read().unwrap()),
+            [
+                (Some("9:30".to_owned()), "зарядка".to_owned()),
+                (Some("12:15".to_owned()), "обед".to_owned()),
+                (Some("18:00".to_owned()), "ужин".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_storage_keeps_colon_in_target() {
+        let path = temp_path("colon.txt");
+        let storage = storage(&path);
+        storage
+            .save(entry("10:00", "встреча: обсудить план").into())
+            .unwrap();
+
+        let list = storage.read().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].target(), "встреча: обсудить план");
+        assert_eq!(list[0].category(), DEFAULT_CATEGORY);
+    }
+
+    #[test]
+    fn text_storage_reads_empty_file() {
+        let path = temp_path("empty.txt");
+        write(&path, "").unwrap();
+
+        assert!(storage(&path).read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn text_storage_ignores_trailing_blank_lines() {
+        let path = temp_path("trailing.txt");
+        write(&path, "9:00\nзарядка\n\n10:00\nзавтрак\n\n\n\n").unwrap();
+
+        assert_eq!(
+            summary(&storage(&path).read().unwrap()),
+            [
+                (Some("9:00".to_owned()), "зарядка".to_owned()),
+                (Some("10:00".to_owned()), "завтрак".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_storage_round_trip_keeps_three_entries_unchanged() {
+        let path = temp_path("round_trip.json");
+        let storage = storage(&path);
+        let entries = [
+            Entry {
+                priority: 1,
+                category: "работа".to_owned(),
+                duration_minutes: Some(45),
+                ..entry("9:00", "отчёт")
+            },
+            Entry {
+                done: true,
+                notes: "молоко\nхлеб".to_owned(),
+                ..entry("12:30", "магазин")
+            },
+            Entry {
+                time: None,
+                recurrence: Some(Recurrence::Daily),
+                ..entry("0:00", "прогулка")
+            },
+        ];
+        for entry in &entries {
+            storage.save(entry.clone().into()).unwrap();
+        }
+
+        // ID назначается при сохранении, остальные поля не меняются
+        let json = |entry: &dyn EntryTrait| {
+            let mut value = serde_json::Value::from(entry);
+            value.as_object_mut().unwrap().remove("id");
+            value
+        };
+        let read = storage.read().unwrap();
+        assert_eq!(
+            read.iter()
+                .map(|entry| json(entry.as_ref()))
+                .collect::<Vec<_>>(),
+            entries.iter().map(|entry| json(entry)).collect::<Vec<_>>()
+        );
+        assert!(read.iter().all(|entry| entry.id().is_some()));
+    }
+
+    #[test]
+    fn partial_tmp_write_does_not_corrupt_planner() {
+        let path = temp_path("atomic.txt");
+        let storage = storage(&path);
+        storage.save(entry("9:00", "зарядка").into()).unwrap();
+        let original = read_to_string(&path).unwrap();
+
+        // Прерванная запись оставляет недописанный временный файл
+        write(format!("{}.tmp", path), "9:3").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), original);
+        assert_eq!(
+            summary(&storage.read().unwrap()),
+            [(Some("9:00".to_owned()), "зарядка".to_owned())]
+        );
+
+        // Следующее сохранение пишет временный файл заново
+        storage.save(entry("10:00", "завтрак").into()).unwrap();
+        assert_eq!(storage.read().unwrap().len(), 2);
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+    }
+
+    #[test]
+    fn sorting_survives_broken_times_in_file() {
+        let path = temp_path("broken_time.txt");
+        write(
+            &path,
+            "25:99\nсломано\n\n18:00\nужин\n\nxx\nтоже сломано\n\n-\nбез времени\n\n7:05\nзарядка\n\n",
+        )
+        .unwrap();
+
+        // Записи с неверным временем пропускаются, а не роняют сортировку
+        let mut list = storage(&path).read().unwrap();
+        list.sort();
+        assert_eq!(
+            summary(&list),
+            [
+                (Some("7:05".to_owned()), "зарядка".to_owned()),
+                (Some("18:00".to_owned()), "ужин".to_owned()),
+                (None, "без времени".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_file_reads_every_block_up_to_eof() {
+        let path = temp_path("large.txt");
+        let count = 5000;
+        let mut buf = (0..count)
+            .map(|i| format!("{}:{:02}\nзадача {}\n", (i / 60) % 24, i % 60, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        // Последний блок без завершающей пустой строки и перевода строки
+        buf.pop();
+        write(&path, buf).unwrap();
+
+        let list = storage(&path).read().unwrap();
+        assert_eq!(list.len(), count);
+        assert!(list
+            .iter()
+            .enumerate()
+            .all(|(i, entry)| entry.target() == format!("задача {}", i)));
+    }
+
+    #[test]
+    fn concurrent_saves_lose_no_entries() {
+        let path = temp_path("concurrent.txt");
+        let per_thread = 25;
+
+        // У каждой копии программы свое хранилище, общий у них только файл
+        let threads = (0..2)
+            .map(|thread| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for i in 0..per_thread {
+                        let target = format!("поток {} задача {}", thread, i);
+                        storage(&path).save(entry("9:00", &target).into()).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let list = storage(&path).read().unwrap();
+        assert_eq!(list.len(), 2 * per_thread);
+        let ids = list
+            .iter()
+            .filter_map(|entry| entry.id())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), 2 * per_thread);
+    }
+
+    #[test]
+    fn time_parses_valid_and_rejects_invalid_strings() {
+        let valid = [
+            ("9:30", "9:30"),
+            ("09:05", "9:05"),
+            ("0:00", "0:00"),
+            ("23:59", "23:59"),
+            (" 7:45 ", "7:45"),
+            ("9.30", "9:30"),
+            ("9 30", "9:30"),
+            ("9-30", "9:30"),
+            ("2:30 PM", "14:30"),
+            ("12:15 am", "0:15"),
+            ("12:00pm", "12:00"),
+        ];
+        for (input, expected) in valid {
+            let time = input.parse::<Time>();
+            assert_eq!(
+                time.map(|time| time.to_string()).ok().as_deref(),
+                Some(expected),
+                "{:?}",
+                input
+            );
+        }
+
+        let invalid = [
+            "",
+            "930",
+            "24:00",
+            "9:60",
+            "ab:cd",
+            ":30",
+            "9:",
+            "13:00 pm",
+            "0:30 am",
+            "полдень",
+        ];
+        for input in invalid {
+            assert!(input.parse::<Time>().is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn outside_change_is_merged_and_selection_remapped() {
+        let path = temp_path("outside.txt");
+        let ours = storage(&path);
+        ours.save(entry("9:00", "наша").into()).unwrap();
+
+        // Другая копия программы добавляет запись между чтением и
+        // сохранением, и она не теряется
+        std::thread::sleep(Duration::from_millis(20));
+        storage(&path).save(entry("8:00", "чужая").into()).unwrap();
+        ours.save(entry("10:00", "новая").into()).unwrap();
+        assert_eq!(ours.read().unwrap().len(), 3);
+
+        // Номер 2 в увиденном списке - "наша", после удаления извне первой
+        // записи она стала первой, и удаляется именно она
+        std::thread::sleep(Duration::from_millis(20));
+        storage(&path).delete(0).unwrap();
+        ours.delete(1).unwrap();
+
+        assert_eq!(
+            summary(&storage(&path).read().unwrap()),
+            [(Some("10:00".to_owned()), "новая".to_owned())]
+        );
+    }
+
+    /// Писатель, который отказывает заданное число раз
+    struct FailingWriter {
+        failures: u32,
+        kind: ErrorKind,
+        calls: u32,
+        written: Vec<u8>,
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.calls += 1;
+            if self.calls <= self.failures {
+                return Err(self.kind.into());
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn failing_writer(failures: u32, kind: ErrorKind) -> FailingWriter {
+        FailingWriter {
+            failures,
+            kind,
+            calls: 0,
+            written: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn transient_write_errors_are_retried() {
+        let storage = storage(&temp_path("retry.txt"));
+        let mut writer = failing_writer(2, ErrorKind::Interrupted);
+        storage
+            .retry(|| writer.write_all(b"9:00 - 1 - a\n"))
+            .unwrap();
+        assert_eq!(writer.calls, 3);
+        assert_eq!(writer.written, b"9:00 - 1 - a\n");
+    }
+
+    #[test]
+    fn other_write_errors_fail_immediately() {
+        let storage = storage(&temp_path("no_retry.txt"));
+        let mut writer = failing_writer(2, ErrorKind::PermissionDenied);
+        let error = storage.retry(|| writer.write_all(b"a")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(writer.calls, 1);
+        assert!(writer.written.is_empty());
+    }
+
+    #[test]
+    fn write_gives_up_after_retry_limit() {
+        let storage = storage(&temp_path("retry_limit.txt"));
+        let mut writer = failing_writer(u32::MAX, ErrorKind::TimedOut);
+        let error = storage.retry(|| writer.write_all(b"a")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert_eq!(writer.calls, DEFAULT_WRITE_RETRIES + 1);
+    }
+
+    #[test]
+    fn yaml_storage_round_trip_keeps_entries_unchanged() {
+        let path = temp_path("round_trip.yaml");
+        let storage = storage(&path);
+        let entries = [
+            Entry {
+                category: "yes".to_owned(),
+                notes: "цитата: \"в кавычках\" # не комментарий".to_owned(),
+                ..entry("9:00", "42")
+            },
+            Entry {
+                done: true,
+                priority: 1,
+                notes: "молоко\nхлеб".to_owned(),
+                ..entry("12:30", "true")
+            },
+            Entry {
+                time: None,
+                label: Some("it's".to_owned()),
+                ..entry("0:00", "~")
+            },
+        ];
+        for entry in &entries {
+            storage.save(entry.clone().into()).unwrap();
+        }
+
+        let json = |entry: &dyn EntryTrait| {
+            let mut value = serde_json::Value::from(entry);
+            value.as_object_mut().unwrap().remove("id");
+            value
+        };
+        assert_eq!(
+            storage
+                .read()
+                .unwrap()
+                .iter()
+                .map(|entry| json(entry.as_ref()))
+                .collect::<Vec<_>>(),
+            entries.iter().map(|entry| json(entry)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn yaml_storage_reads_hand_edited_scalars() {
+        let path = temp_path("hand.yaml");
+        std::fs::write(
+            &path,
+            "# правка вручную\n\
+             - time: 9:00\n  target: 42\n\
+             - time: 10:00\n  target: yes\n  done: yes\n  priority: \"2\"\n\
+             - time: 11:00\n  target: зарядка # утром\n  category: 'дом'\n  done: off\n",
+        )
+        .unwrap();
+
+        let list = storage(&path).read().unwrap();
+        assert_eq!(
+            summary(&list),
+            [
+                (Some("9:00".to_owned()), "42".to_owned()),
+                (Some("10:00".to_owned()), "yes".to_owned()),
+                (Some("11:00".to_owned()), "зарядка".to_owned()),
+            ]
+        );
+        assert!(list[1].done());
+        assert_eq!(list[1].priority(), 2);
+        assert_eq!(list[2].category(), "дом");
+        assert!(!list[2].done());
+
+        // Неверное значение числового поля по-прежнему пропускает запись
+        std::fs::write(&path, "- time: 9:00\n  target: a\n  priority: high\n").unwrap();
+        assert!(storage(&path).validate().is_err());
+    }
+
+    #[test]
+    fn dry_run_leaves_no_files() {
+        let dir = temp_path("dry_run");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = Path::new(&dir).join("planner.txt");
+        let storage = storage(&path.to_string_lossy())
+            .with_create_dirs(true)
+            .with_dry_run(true);
+
+        assert!(storage.read().unwrap().is_empty());
+        storage.save(entry("9:00", "зарядка").into()).unwrap();
+        assert!(!Path::new(&dir).exists());
+
+        // В существующем каталоге не появляются ни файл планера, ни
+        // файл блокировки
+        create_dir_all(&dir).unwrap();
+        assert!(storage.read().unwrap().is_empty());
+        storage.save(entry("9:00", "зарядка").into()).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn missing_dir_is_created_only_by_write() {
+        let dir = temp_path("created_dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = Path::new(&dir).join("planner.jsonl");
+        let storage = storage(&path.to_string_lossy()).with_create_dirs(true);
+
+        // Неудачное изменение не оставляет пустой каталог
+        assert!(storage.delete(0).is_err());
+        assert!(!Path::new(&dir).exists());
+
+        storage.save(entry("9:00", "зарядка").into()).unwrap();
+        assert_eq!(storage.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn json_lines_append_skips_backups_and_undo_truncates() {
+        let path = temp_path("append.jsonl");
+        let storage = storage(&path);
+        for i in 0..200 {
+            let time = format!("{}:{:02}", 23 - i / 60 % 24, 59 - i % 60);
+            storage
+                .save(entry(&time, &format!("задача {}", i)).into())
+                .unwrap();
+        }
+        assert!(!Path::new(&format!("{}.bak", path)).exists());
+        assert!(!Path::new(&format!("{}.1", path)).exists());
+
+        let list = storage.read().unwrap();
+        assert_eq!(list.len(), 200);
+        assert!(list.windows(2).all(|pair| pair[0].cmp(&pair[1]).is_le()));
+        let ids: HashSet<_> = list.iter().filter_map(|entry| entry.id()).collect();
+        assert_eq!(ids.len(), 200);
+
+        // Отмена убирает только последнюю дописанную запись
+        storage.undo().unwrap();
+        let list = storage.read().unwrap();
+        assert_eq!(list.len(), 199);
+        assert!(list.iter().all(|entry| entry.target() != "задача 199"));
+        assert!(storage.undo().is_err());
+    }
+
+    #[test]
+    fn entry_equality_agrees_with_ordering() {
+        let boxed = |entry: Entry| -> Box<dyn EntryTrait> { entry.into() };
+        let entries = [
+            boxed(entry("9:00", "зарядка")),
+            boxed(entry("9:00", "  Зарядка ")),
+            boxed(entry("9:00", "чтение")),
+            boxed(Entry {
+                order: 1,
+                ..entry("9:00", "зарядка")
+            }),
+            boxed(Entry {
+                priority: 1,
+                ..entry("9:00", "зарядка")
+            }),
+            boxed(Entry {
+                time: None,
+                ..entry("9:00", "зарядка")
+            }),
+        ];
+        for a in &entries {
+            for b in &entries {
+                assert_eq!(a == b, a.cmp(b).is_eq(), "{} / {}", a.target(), b.target());
+            }
+        }
+        assert!(entries[0].cmp(&entries[1]).is_eq());
+        assert!(entries[0].cmp(&entries[2]).is_lt());
+    }
+
+    #[test]
+    fn rename_category_renames_all_and_rejects_invalid_names() {
+        let path = temp_path("recategorize.txt");
+        let storage = storage(&path);
+        for (time, category) in [("9:00", "Работа"), ("10:00", "дом"), ("11:00", "работа")]
+        {
+            let entry = Entry {
+                category: category.to_owned(),
+                ..entry(time, "задача")
+            };
+            storage.save(entry.into()).unwrap();
+        }
+
+        assert_eq!(storage.rename_category("работа", "офис", true).unwrap(), 2);
+        let categories = storage
+            .read()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.category().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(categories, ["офис", "дом", "офис"]);
+
+        for name in ["", "  ", "а\nб", "a\tb"] {
+            assert!(storage.rename_category("офис", name, false).is_err());
+        }
+        assert_eq!(storage.read().unwrap()[0].category(), "офис");
+    }
+
+    #[test]
+    fn greeting_counts_todays_entries() {
+        let list = boxed(&[
+            entry("9:00", "зарядка"),
+            entry("13:00", "обед"),
+            Entry {
+                date: "2024-06-02".to_owned(),
+                ..entry("10:00", "завтра")
+            },
+        ]);
+        assert_eq!(
+            HelloModel::greeting(list, at("10:00")),
+            "Привет! На сегодня задач: 2 (ближайшая в 13:00)"
+        );
+
+        let list = boxed(&[entry("9:00", "зарядка")]);
+        assert_eq!(
+            HelloModel::greeting(list, at("10:00")),
+            "Привет! На сегодня задач: 1"
+        );
+        assert_eq!(HelloModel::greeting(Vec::new(), at("10:00")), "Привет!");
+    }
+
+    #[test]
+    fn focus_finds_current_and_next_entries() {
+        let list = || {
+            boxed(&[
+                Entry {
+                    duration_minutes: Some(30),
+                    ..entry("9:00", "зарядка")
+                },
+                entry("12:00", "обед"),
+            ])
+        };
+        let targets = |focus: Focus| {
+            (
+                focus.current.map(|entry| entry.target().to_owned()),
+                focus.next.map(|entry| entry.target().to_owned()),
+            )
+        };
+
+        // Посреди задачи
+        assert_eq!(
+            targets(FocusModel::focus(list(), at("9:10"))),
+            (Some("зарядка".to_owned()), Some("обед".to_owned()))
+        );
+        // Между задачами: первая закончилась по длительности
+        assert_eq!(
+            targets(FocusModel::focus(list(), at("10:00"))),
+            (None, Some("обед".to_owned()))
+        );
+        // После последней задачи без длительности она остается текущей
+        assert_eq!(
+            targets(FocusModel::focus(list(), at("20:00"))),
+            (Some("обед".to_owned()), None)
+        );
+    }
+
+    #[test]
+    fn gaps_lie_between_busy_intervals() {
+        let time = |value: &str| value.parse::<Time>().unwrap();
+        let span = |from: &str, to: &str| (time(from), time(to));
+        let gaps = |busy: &[(Time, Time)]| GapsModel::gaps(busy, time("8:00"), time("12:00"));
+
+        // Задачи подряд без промежутка, свободно только в начале окна
+        assert_eq!(
+            gaps(&[span("10:00", "11:00"), span("11:00", "12:00")]),
+            [span("8:00", "10:00")]
+        );
+        // Свободно в конце окна
+        assert_eq!(gaps(&[span("8:00", "9:30")]), [span("9:30", "12:00")]);
+        // Пересекающиеся задачи занимают общий промежуток
+        assert_eq!(
+            gaps(&[span("10:00", "11:30"), span("9:00", "10:30")]),
+            [span("8:00", "9:00"), span("11:30", "12:00")]
+        );
+        // Задача, начавшаяся до окна, и задача после него
+        assert_eq!(
+            gaps(&[span("7:00", "8:30"), span("13:00", "14:00")]),
+            [span("8:30", "12:00")]
+        );
+        assert_eq!(gaps(&[]), [span("8:00", "12:00")]);
+        assert!(gaps(&[span("7:00", "13:00")]).is_empty());
+    }
+
+    #[test]
+    fn next_entry_starts_at_or_after_now() {
+        let list = || {
+            boxed(&[
+                entry("9:00", "зарядка"),
+                Entry {
+                    done: true,
+                    ..entry("11:00", "сделано")
+                },
+                entry("12:00", "обед"),
+            ])
+        };
+        let next = |now: &str| {
+            NextEntryModel::find(list(), at(now)).map(|entry| entry.target().to_owned())
+        };
+
+        assert_eq!(next("10:00").as_deref(), Some("обед"));
+        // Запись ровно в текущую минуту еще ближайшая, секунды не считаются
+        assert_eq!(next("12:00").as_deref(), Some("обед"));
+        assert_eq!(
+            NextEntryModel::find(list(), at("12:00") + chrono::Duration::seconds(30))
+                .map(|entry| entry.target().to_owned())
+                .as_deref(),
+            Some("обед")
+        );
+        assert_eq!(next("12:01"), None);
+    }
+
+    #[test]
+    fn relative_times_resolve_from_now() {
+        let now = NaiveTime::from_hms_opt(9, 15, 40).unwrap();
+        let resolve = |input: &str| {
+            Entry::resolve_time(input, now)
+                .map(|time| time.to_string())
+                .ok()
+        };
+
+        assert_eq!(resolve("сейчас").as_deref(), Some("9:15"));
+        assert_eq!(resolve(" Сейчас ").as_deref(), Some("9:15"));
+        assert_eq!(resolve("+30").as_deref(), Some("9:45"));
+        assert_eq!(resolve("+1:30").as_deref(), Some("10:45"));
+        assert_eq!(resolve("+ 45").as_deref(), Some("10:00"));
+        assert_eq!(resolve("+1:60"), None);
+        assert_eq!(resolve("+abc"), None);
+
+        // Время после 23:59 ограничивается концом дня
+        let late = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
+        let clamped = |input: &str| Entry::resolve_time(input, late).unwrap().to_string();
+        assert_eq!(clamped("+90"), "23:59");
+        assert_eq!(clamped("+48:00"), "23:59");
+        assert_eq!(clamped("+59"), "23:59");
+    }
+
+    #[test]
+    fn recurring_entries_expand_across_week_boundary() {
+        let day = |day: u32| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let list = boxed(&[
+            Entry {
+                date: "2024-06-08".to_owned(),
+                recurrence: Some(Recurrence::Daily),
+                ..entry("7:00", "зарядка")
+            },
+            Entry {
+                recurrence: Some(Recurrence::Weekly(Weekday::Mon)),
+                ..entry("10:00", "планерка")
+            },
+            entry("12:00", "вне диапазона"),
+            Entry {
+                date: String::new(),
+                ..entry("20:00", "без даты")
+            },
+        ]);
+
+        // С пятницы 7 июня по вторник 11 июня
+        let expanded = expand_range(list, day(7), day(11));
+        let dates = expanded
+            .iter()
+            .map(|entry| (entry.date().to_owned(), entry.target().to_owned()))
+            .collect::<Vec<_>>();
+        let pair = |date: &str, target: &str| (date.to_owned(), target.to_owned());
+        assert_eq!(
+            dates,
+            [
+                pair("", "без даты"),
+                pair("2024-06-08", "зарядка"),
+                pair("2024-06-09", "зарядка"),
+                pair("2024-06-10", "зарядка"),
+                pair("2024-06-10", "планерка"),
+                pair("2024-06-11", "зарядка"),
+            ]
+        );
+
+        assert!(!Recurrence::Daily.occurs_on("2024-06-08", day(7)));
+        assert!(Recurrence::Daily.occurs_on("", day(7)));
+        assert!(Recurrence::Weekly(Weekday::Mon).occurs_on("2024-06-01", day(3)));
+        assert!(!Recurrence::Weekly(Weekday::Mon).occurs_on("2024-06-01", day(4)));
+    }
+
+    #[test]
+    fn reversed_list_keeps_untimed_entries_last() {
+        let list = || {
+            boxed(&[
+                entry("9:00", "зарядка"),
+                Entry {
+                    time: None,
+                    ..entry("0:00", "прогулка")
+                },
+                entry("12:00", "обед"),
+                entry("18:00", "ужин"),
+            ])
+        };
+        let order = |output: String| {
+            let mut targets = ["зарядка", "обед", "ужин", "прогулка"]
+                .map(|target| (output.find(target).unwrap(), target));
+            targets.sort();
+            targets.map(|(_, target)| target)
+        };
+
+        assert_eq!(
+            order(ListView::default().render(list())),
+            ["зарядка", "обед", "ужин", "прогулка"]
+        );
+        assert_eq!(
+            order(ListView::default().with_reverse(true).render(list())),
+            ["ужин", "обед", "зарядка", "прогулка"]
+        );
+    }
+
+    #[test]
+    fn import_skips_only_exact_duplicates() {
+        let path = temp_path("import.txt");
+        let other = temp_path("import_other.txt");
+        storage(&path)
+            .save(entry("9:00", "зарядка").into())
+            .unwrap();
+        let imported = [
+            entry("9:00", "зарядка"),
+            entry("9:00", "Зарядка"),
+            Entry {
+                done: true,
+                ..entry("9:00", "зарядка")
+            },
+            Entry {
+                category: "спорт".to_owned(),
+                ..entry("9:00", "зарядка")
+            },
+            Entry {
+                notes: "10 минут".to_owned(),
+                ..entry("9:00", "зарядка")
+            },
+        ];
+        storage(&other).save_all(boxed(&imported)).unwrap();
+
+        assert_eq!(storage(&path).import(&other).unwrap(), 4);
+        assert_eq!(storage(&path).read().unwrap().len(), 5);
+        assert_eq!(storage(&path).import(&other).unwrap(), 0);
+    }
+
+    #[test]
+    fn list_renders_zero_one_and_several_entries() {
+        let separators = |output: &str| {
+            output
+                .lines()
+                .filter(|line| !line.is_empty() && line.chars().all(|c| c == '-'))
+                .count()
+        };
+
+        let empty = ListView::default().render(Vec::new());
+        assert_eq!(
+            empty,
+            "====================================\nМое расписание:\n\n\
+             Расписание пусто. Добавь первую задачу!\n\n\
+             ====================================\n"
+        );
+
+        let one = ListView::default().render(boxed(&[entry("9:00", "зарядка")]));
+        assert!(one.contains("Задача: [ ] зарядка\n"));
+        assert!(one.contains("Всего задач: 1 (выполнено: 0)"));
+        assert_eq!(separators(&one), 0);
+
+        let several = ListView::default().render(boxed(&[
+            entry("9:00", "зарядка"),
+            entry("12:00", "обед"),
+            Entry {
+                done: true,
+                ..entry("18:00", "ужин")
+            },
+        ]));
+        assert!(several.contains("Задача: [x] ужин\n"));
+        assert!(several.contains("Всего задач: 3 (выполнено: 1)"));
+        assert_eq!(separators(&several), 2);
+    }
+
+    #[test]
+    fn summary_counts_all_and_done_entries() {
+        assert_eq!(
+            ListView::summary(&[]),
+            "Всего задач: 0 (выполнено: 0)\nнет задач"
+        );
+        let list = boxed(&[
+            Entry {
+                done: true,
+                ..entry("9:00", "зарядка")
+            },
+            entry("12:00", "обед"),
+        ]);
+        assert_eq!(
+            ListView::summary(&list),
+            "Всего задач: 2 (выполнено: 1)\n[#####-----] 50%"
+        );
+    }
+
+    #[test]
+    fn progress_bar_rounds_down_to_tenths() {
+        assert_eq!(ListView::progress(0, 0), "нет задач");
+        assert_eq!(ListView::progress(0, 4), "[----------] 0%");
+        assert_eq!(ListView::progress(1, 3), "[###-------] 33%");
+        assert_eq!(ListView::progress(2, 3), "[######----] 66%");
+        assert_eq!(ListView::progress(3, 3), "[##########] 100%");
+        // Выполненных больше, чем всего, не бывает больше 100%
+        assert_eq!(ListView::progress(5, 3), "[##########] 100%");
+    }
+
+    /// Разбор CSV с полями в кавычках, удвоенными кавычками и переводами
+    /// строк внутри полей
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let (mut row, mut field) = (Vec::new(), String::new());
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', _) => quoted = !quoted,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                ('\n', false) => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+
+        rows
+    }
+
+    #[test]
+    fn csv_export_quotes_commas_quotes_and_newlines() {
+        let path = temp_path("export.json");
+        let csv = temp_path("export.csv");
+        let cases = [
+            ("9:00", "обед, потом кофе"),
+            ("10:00", "книга \"Война и мир\""),
+            ("11:00", "строка\nвторая"),
+        ];
+        let entries = cases.map(|(time, target)| Entry {
+            category: "дом,сад".to_owned(),
+            ..entry(time, target)
+        });
+        storage(&path).save_all(boxed(&entries)).unwrap();
+        storage(&path).export_csv(&csv).unwrap();
+
+        let rows = parse_csv(&std::fs::read_to_string(&csv).unwrap());
+        assert_eq!(
+            rows[0],
+            [
+                "date",
+                "time",
+                "target",
+                "category",
+                "priority",
+                "duration_minutes",
+                "done"
+            ]
+        );
+        assert_eq!(rows.len(), 4);
+        for (row, (time, target)) in rows[1..].iter().zip(cases) {
+            assert_eq!(
+                row,
+                &["2024-06-01", time, target, "дом,сад", "3", "", "false"]
+            );
+        }
+        assert_eq!(csv_field("просто"), "просто");
+    }
+
+    #[test]
+    fn ics_export_has_start_rule_escaping_and_folding() {
+        let path = temp_path("export_ics.json");
+        let ics = temp_path("export.ics");
+        let long = "очень длинная задача ".repeat(5);
+        let entries = [
+            Entry {
+                duration_minutes: Some(90),
+                recurrence: Some(Recurrence::Weekly(Weekday::Mon)),
+                notes: "первая строка\nвторая; третья, \\ конец".to_owned(),
+                ..entry("9:30", "планерка")
+            },
+            Entry {
+                time: None,
+                recurrence: Some(Recurrence::Daily),
+                ..entry("0:00", long.trim())
+            },
+        ];
+        storage(&path).save_all(boxed(&entries)).unwrap();
+        storage(&path).export_ics(&ics).unwrap();
+
+        let text = std::fs::read_to_string(&ics).unwrap();
+        let lines = text.split("\r\n").collect::<Vec<_>>();
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines.contains(&"DTSTART:20240601T093000"));
+        assert!(lines.contains(&"DTEND:20240601T110000"));
+        assert!(lines.contains(&"RRULE:FREQ=WEEKLY;BYDAY=MO"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240601"));
+        assert!(lines.contains(&"RRULE:FREQ=DAILY"));
+
+        // Перенесенные строки склеиваются обратно без потерь
+        let unfolded = text.replace("\r\n ", "");
+        assert!(unfolded.contains("DESCRIPTION:первая строка\\nвторая\\; третья\\, \\\\ конец\r\n"));
+        assert!(unfolded.contains(&format!("SUMMARY:{}\r\n", long.trim())));
+    }
+
+    #[test]
+    fn ics_fold_keeps_short_lines_and_whole_characters() {
+        assert_eq!(ics_fold("SUMMARY:кратко"), "SUMMARY:кратко");
+        let folded = ics_fold(&format!("SUMMARY:{}", "я".repeat(60)));
+        let parts = folded.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|part| part.len() <= 75));
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(
+            folded.replace("\r\n ", ""),
+            format!("SUMMARY:{}", "я".repeat(60))
+        );
+    }
+
+    #[test]
+    fn clear_empties_planner_and_undo_restores_it() {
+        let path = temp_path("clear.txt");
+        let storage = storage(&path);
+        storage
+            .save_all(boxed(&[entry("9:00", "зарядка"), entry("12:00", "обед")]))
+            .unwrap();
+
+        storage.clear().unwrap();
+        assert!(storage.read().unwrap().is_empty());
+
+        storage.undo().unwrap();
+        assert_eq!(
+            summary(&storage.read().unwrap()),
+            [
+                (Some("9:00".to_owned()), "зарядка".to_owned()),
+                (Some("12:00".to_owned()), "обед".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_restores_previous_state_only_once() {
+        let path = temp_path("undo.txt");
+        let storage = storage(&path);
+        assert_eq!(storage.undo().unwrap_err().to_string(), "Нечего отменять.");
+
+        storage.save(entry("9:00", "зарядка").into()).unwrap();
+        storage.save(entry("12:00", "обед").into()).unwrap();
+        storage.undo().unwrap();
+        assert_eq!(
+            summary(&storage.read().unwrap()),
+            [(Some("9:00".to_owned()), "зарядка".to_owned())]
+        );
+
+        // Хранится только одно предыдущее состояние
+        assert_eq!(storage.undo().unwrap_err().to_string(), "Нечего отменять.");
+        assert_eq!(storage.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn config_parses_known_values_and_skips_unknown() {
+        let config = Config::parse(
+            "storage_path = \"~/planner.json\"\n\
+             lang = \"en\"\n\
+             max_entries = 50\n\
+             day_start_hour = 4\n\
+             work_start = \"9:00\"\n\
+             text_mirror = true\n\
+             sort_mode = \"created\"\n\
+             unknown = 1\n",
+        )
+        .unwrap();
+        assert_eq!(config.storage_path.as_deref(), Some("~/planner.json"));
+        assert_eq!(config.lang, Some(Lang::En));
+        assert_eq!(config.max_entries, Some(50));
+        assert_eq!(config.day_start_hour, Some(4));
+        assert_eq!(config.work_start, Some("9:00".parse().unwrap()));
+        assert_eq!(config.text_mirror, Some(true));
+        assert_eq!(config.sort_mode, Some(SortMode::Created));
+        assert_eq!(config.backups, None);
+
+        // Неверное значение не ломает остальные настройки
+        let config = Config::parse("max_entries = \"много\"\nbackups = 2\n").unwrap();
+        assert_eq!(config.max_entries, None);
+        assert_eq!(config.backups, Some(2));
+        assert!(Config::parse("storage_path = ").is_err());
+    }
+
+    #[test]
+    fn week_buckets_cover_seven_days_from_start() {
+        let day = |day: u32| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let list = boxed(&[
+            entry("9:00", "суббота"),
+            Entry {
+                date: "2024-06-07".to_owned(),
+                ..entry("12:00", "обед")
+            },
+            Entry {
+                date: "2024-06-07".to_owned(),
+                ..entry("8:00", "завтрак")
+            },
+            Entry {
+                date: "2024-06-08".to_owned(),
+                ..entry("10:00", "за пределами недели")
+            },
+            Entry {
+                recurrence: Some(Recurrence::Weekly(Weekday::Mon)),
+                ..entry("10:00", "планерка")
+            },
+            Entry {
+                date: String::new(),
+                ..entry("20:00", "без даты")
+            },
+        ]);
+
+        // Неделя с субботы 1 июня по пятницу 7 июня
+        let buckets = WeekViewModel::buckets(list, day(1));
+        assert_eq!(
+            buckets.iter().map(|(date, _)| *date).collect::<Vec<_>>(),
+            (1..=7).map(day).collect::<Vec<_>>()
+        );
+        let targets = buckets
+            .iter()
+            .map(|(_, list)| {
+                summary(list)
+                    .into_iter()
+                    .map(|(_, target)| target)
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>();
+        assert_eq!(
+            targets,
+            [
+                vec!["суббота"],
+                vec![],
+                vec!["планерка"],
+                vec![],
+                vec![],
+                vec![],
+                vec!["завтрак", "обед"],
+            ]
+        );
+    }
+
+    #[test]
+    fn json_view_reads_back_as_the_same_entries() {
+        let list = boxed(&[
+            Entry {
+                id: Some(3),
+                priority: 2,
+                label: Some("важно".to_owned()),
+                ..entry("9:00", "отчёт \"за май\"")
+            },
+            Entry {
+                done: true,
+                notes: "молоко\nхлеб".to_owned(),
+                ..entry("12:30", "магазин")
+            },
+            Entry {
+                time: None,
+                recurrence: Some(Recurrence::Weekly(Weekday::Fri)),
+                ..entry("0:00", "уборка")
+            },
+        ]);
+
+        let read = Storage::parse_json(&JsonView.render(&list)).unwrap();
+        let json = |list: &[Box<dyn EntryTrait>]| {
+            list.iter()
+                .map(|entry| serde_json::Value::from(entry.as_ref()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(json(&read), json(&list));
+        assert_eq!(JsonView.render(&[]), "[]");
+    }
+
+    #[test]
+    fn markdown_storage_round_trip_keeps_entries_unchanged() {
+        let path = temp_path("round_trip.md");
+        let storage = storage(&path);
+        let entries = [
+            Entry {
+                category: "работа".to_owned(),
+                duration_minutes: Some(30),
+                reminder_minutes: Some(10),
+                ..entry("9:00", "отчёт — черновик")
+            },
+            Entry {
+                done: true,
+                priority: 1,
+                notes: "молоко\nхлеб".to_owned(),
+                ..entry("12:30", "магазин")
+            },
+            Entry {
+                time: None,
+                label: Some("дом".to_owned()),
+                recurrence: Some(Recurrence::Weekly(Weekday::Sun)),
+                ..entry("0:00", "уборка")
+            },
+        ];
+        for entry in &entries {
+            storage.save(entry.clone().into()).unwrap();
+        }
+
+        let text = read_to_string(&path).unwrap();
+        assert!(text.contains("- [ ] 9:00 — отчёт — черновик\n"));
+        assert!(text.contains("- [x] 12:30 — магазин\n"));
+        assert!(text.contains("- [ ] уборка\n"));
+        assert!(text.contains("  - category: работа\n"));
+
+        let json = |entry: &dyn EntryTrait| {
+            let mut value = serde_json::Value::from(entry);
+            value.as_object_mut().unwrap().remove("id");
+            value
+        };
+        assert_eq!(
+            storage
+                .read()
+                .unwrap()
+                .iter()
+                .map(|entry| json(entry.as_ref()))
+                .collect::<Vec<_>>(),
+            entries.iter().map(|entry| json(entry)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn usage_lists_every_command_and_flag() {
+        let usage = App::usage();
+        assert!(usage.starts_with("Использование: my-planner [команда] [флаги]\n"));
+
+        let listed = |name: &str| {
+            usage
+                .lines()
+                .any(|line| line.trim_start().split([' ', ',']).next() == Some(name))
+        };
+        for command in [
+            "add",
+            "list",
+            "tomorrow",
+            "week",
+            "next",
+            "focus",
+            "gaps",
+            "stats",
+            "delete",
+            "edit",
+            "move",
+            "copy",
+            "swap",
+            "merge",
+            "search",
+            "done",
+            "clear",
+            "undo",
+            "archive",
+            "apply-template",
+            "profiles",
+            "where",
+            "rollover",
+            "restore",
+            "shift",
+            "recategorize",
+            "import",
+            "export",
+        ] {
+            assert!(listed(command), "{}", command);
+        }
+        for flag in [
+            "--all",
+            "--category",
+            "--json",
+            "--page",
+            "--output",
+            "--compact",
+            "--reverse",
+            "--id",
+            "--from",
+            "--file",
+            "--profile",
+            "--sort",
+            "--lang",
+            "--batch",
+            "--multi",
+            "--tomorrow",
+            "--if-not-exists",
+            "--dry-run",
+            "--strict",
+            "--skip-validation",
+            "-i",
+            "-q",
+            "-h",
+            "-V",
+        ] {
+            assert!(listed(flag), "{}", flag);
+        }
+        for flag in [
+            "--csv",
+            "--ics",
+            "--to",
+            "--ignore-case",
+            "--quiet",
+            "--help",
+            "--version",
+        ] {
+            assert!(usage.contains(flag), "{}", flag);
+        }
+    }
+
+    #[test]
+    fn time_separators_are_equal_and_bare_numbers_are_rejected_clearly() {
+        for input in ["9:30", "9.30", "9 30", "9-30", "09 . 30"] {
+            assert_eq!(
+                input.parse::<Time>().unwrap(),
+                "9:30".parse().unwrap(),
+                "{:?}",
+                input
+            );
+        }
+
+        // Число без разделителя не угадывается ни как часы, ни как 9:00
+        for input in ["9", "900", "0930"] {
+            assert_eq!(
+                input.parse::<Time>().unwrap_err().to_string(),
+                "Неверное время: укажите часы и минуты, например 9:30.",
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn soon_status_starts_at_reminder_and_ends_at_start() {
+        let reminded = Entry {
+            reminder_minutes: Some(15),
+            ..entry("12:00", "обед")
+        };
+        let status = |entry: &Entry, now: &str| ListView::status(entry, at(now));
+
+        assert_eq!(status(&reminded, "11:44"), None);
+        assert_eq!(status(&reminded, "11:45"), Some("⏰ скоро"));
+        assert_eq!(status(&reminded, "12:00"), Some("⏰ скоро"));
+        assert_eq!(status(&reminded, "12:01"), Some("прошло"));
+
+        // Без напоминания, выполненная и без времени запись отметки "скоро" не получают
+        assert_eq!(status(&entry("12:00", "обед"), "11:59"), None);
+        let done = Entry {
+            done: true,
+            ..reminded.clone()
+        };
+        assert_eq!(status(&done, "11:50"), None);
+        let untimed = Entry {
+            time: None,
+            ..reminded.clone()
+        };
+        assert_eq!(status(&untimed, "11:50"), None);
+
+        // Запись с длительностью считается идущей до своего конца
+        let long = Entry {
+            duration_minutes: Some(30),
+            ..reminded
+        };
+        assert_eq!(status(&long, "12:20"), None);
+        assert_eq!(status(&long, "12:31"), Some("прошло"));
+    }
+
+    #[test]
+    fn ansi_codes_appear_only_with_color_enabled() {
+        let list = boxed(&[
+            entry("9:00", "зарядка"),
+            Entry {
+                label: Some("red".to_owned()),
+                ..entry("18:00", "ужин")
+            },
+        ]);
+        let view = |color: bool, compact: bool| {
+            ListView::on(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+                .with_now(Some(at("12:00")))
+                .with_color(color)
+                .with_compact(compact)
+                .format_list(&list)
+        };
+
+        for compact in [false, true] {
+            let colored = view(true, compact);
+            assert!(colored.contains("\x1b[36m"), "{:?}", colored);
+            assert!(colored.contains("\x1b[31m"), "{:?}", colored);
+            assert!(colored.contains("\x1b[0m"), "{:?}", colored);
+
+            let plain = view(false, compact);
+            assert!(!plain.contains('\x1b'), "{:?}", plain);
+            assert!(plain.contains("зарядка") && plain.contains("ужин"));
+        }
+    }
+
+    #[test]
+    fn stats_put_noon_and_six_pm_in_their_own_parts() {
+        let list = boxed(&[
+            entry("5:59", "ночь"),
+            entry("6:00", "утро"),
+            entry("11:59", "утро"),
+            entry("12:00", "день"),
+            entry("17:59", "день"),
+            Entry {
+                category: "дом".to_owned(),
+                ..entry("18:00", "вечер")
+            },
+            Entry {
+                time: None,
+                category: "дом".to_owned(),
+                ..entry("0:00", "когда-нибудь")
+            },
+        ]);
+
+        let lines = StatsModel::render(&list)
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>();
+        for expected in [
+            "Общее 5",
+            "дом 2",
+            "Утро 2",
+            "День 2",
+            "Вечер 1",
+            "Ночь 1",
+            "Без времени 1",
+            "Всего задач 7",
+        ] {
+            assert!(lines.iter().any(|line| line == expected), "{}", expected);
+        }
+    }
+
+    #[test]
+    fn paginate_fills_pages_up_to_size() {
+        let sizes = |len: usize, size: usize| {
+            paginate((0..len).collect(), size)
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sizes(6, 3), [3, 3]);
+        assert_eq!(sizes(7, 3), [3, 3, 1]);
+        assert_eq!(sizes(2, 3), [2]);
+        assert_eq!(sizes(0, 3), [0]);
+        // Нулевой размер страницы считается единицей
+        assert_eq!(sizes(2, 0), [1, 1]);
+
+        assert_eq!(
+            paginate(vec![1, 2, 3, 4, 5], 2),
+            [vec![1, 2], vec![3, 4], vec![5]]
+        );
+    }
+
+    #[test]
+    fn next_day_rolls_over_month_and_year() {
+        let moment = |y: i32, m: u32, d: u32, h: u32| {
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, 30, 0)
+                .unwrap()
+        };
+        let date = |y: i32, m: u32, d: u32| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(next_day(moment(2024, 5, 31, 12), 0), date(2024, 6, 1));
+        assert_eq!(next_day(moment(2024, 4, 30, 23), 0), date(2024, 5, 1));
+        assert_eq!(next_day(moment(2024, 2, 28, 9), 0), date(2024, 2, 29));
+        assert_eq!(next_day(moment(2023, 2, 28, 9), 0), date(2023, 3, 1));
+        assert_eq!(next_day(moment(2024, 12, 31, 23), 0), date(2025, 1, 1));
+        // Ночью до начала дня завтра - это еще наступающий календарный день
+        assert_eq!(next_day(moment(2025, 1, 1, 2), 4), date(2025, 1, 1));
+    }
+
+    #[test]
+    fn entry_limit_rejects_one_entry_too_many() {
+        for name in ["limit.txt", "limit.jsonl"] {
+            let path = temp_path(name);
+            let storage = storage(&path).with_max_entries(Some(2));
+            storage.save(entry("9:00", "зарядка").into()).unwrap();
+            storage.save(entry("10:00", "чтение").into()).unwrap();
+
+            let error = storage.save(entry("11:00", "прогулка").into()).unwrap_err();
+            assert_eq!(error.to_string(), "Достигнут лимит записей", "{}", name);
+            assert!(storage.save_all(boxed(&[entry("12:00", "обед")])).is_err());
+            assert_eq!(
+                summary(&storage.read().unwrap()),
+                summary(&boxed(&[
+                    entry("9:00", "зарядка"),
+                    entry("10:00", "чтение")
+                ]))
+            );
+        }
+
+        // Пакет, превышающий лимит, не сохраняется даже частично
+        let path = temp_path("limit_batch.txt");
+        let storage = storage(&path).with_max_entries(Some(2));
+        let batch = boxed(&[entry("9:00", "a"), entry("10:00", "b"), entry("11:00", "c")]);
+        assert!(storage.save_all(batch).is_err());
+        assert!(storage.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_finds_one_none_or_several_entries() {
+        let path = temp_path("search.txt");
+        let storage = storage(&path);
+        for (time, target) in [
+            ("9:00", "Звонок маме"),
+            ("12:00", "обед"),
+            ("18:00", "звонок врачу"),
+        ] {
+            storage.save(entry(time, target).into()).unwrap();
+        }
+
+        assert_eq!(storage.find("обед").unwrap(), [1]);
+        assert_eq!(storage.find("  ЗВОНОК ").unwrap(), [0, 2]);
+        assert!(storage.find("ужин").unwrap().is_empty());
+        // По времени ищется запись на это время, а не текст
+        assert_eq!(storage.find("18:00").unwrap(), [2]);
+        assert_eq!(storage.find("9.00").unwrap(), [0]);
+        assert!(Storage::matches(&[], "обед").is_empty());
+    }
+
+    #[test]
+    fn time_converts_between_fixed_offsets() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let offset = |hours: i32| FixedOffset::east_opt(hours * 3600).unwrap();
+        let convert = |time: &str, from: i32, to: i32| {
+            convert_time(date, time.parse().unwrap(), offset(from), offset(to))
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        };
+
+        assert_eq!(convert("9:30", 3, 3), "2024-06-01 09:30");
+        assert_eq!(convert("9:30", 3, 0), "2024-06-01 06:30");
+        assert_eq!(convert("9:30", 0, 3), "2024-06-01 12:30");
+        assert_eq!(convert("22:00", 3, 5), "2024-06-02 00:00");
+        assert_eq!(convert("1:00", 3, -5), "2024-05-31 17:00");
+        let half = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        assert_eq!(
+            convert_time(date, "12:00".parse().unwrap(), offset(0), half)
+                .format("%H:%M")
+                .to_string(),
+            "17:30"
+        );
+
+        // Запись в местном поясе и запись без пояса не переводятся
+        for offset in [Some(local_offset()), None] {
+            let entry = Entry {
+                offset,
+                ..entry("9:30", "зарядка")
+            };
+            assert_eq!(
+                entry.local_start(),
+                ("2024-06-01".to_owned(), Some("9:30".parse().unwrap()))
+            );
+        }
+    }
+
+    #[test]
+    fn untimed_entries_sort_last_under_their_heading() {
+        let untimed = |target: &str| Entry {
+            time: None,
+            ..entry("0:00", target)
+        };
+        let path = temp_path("untimed.txt");
+        let storage = storage(&path);
+        for entry in [
+            untimed("продукты"),
+            entry("18:00", "ужин"),
+            untimed("книга"),
+            entry("7:00", "зарядка"),
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+
+        let list = storage.read().unwrap();
+        let times = summary(&list)
+            .into_iter()
+            .map(|(time, _)| time)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            times,
+            [
+                Some("7:00".to_owned()),
+                Some("18:00".to_owned()),
+                None,
+                None
+            ]
+        );
+        assert!(list[1].cmp(&list[2]).is_lt());
+
+        let text = ListView::on(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()).render(list);
+        let position = |needle: &str| text.find(needle).unwrap();
+        assert!(position("ужин") < position("Без времени"));
+        assert!(position("Без времени") < position("продукты"));
+        assert!(position("Без времени") < position("книга"));
+    }
+
+    #[test]
+    fn target_check_limits_length_and_control_characters() {
+        let error = |target: &str| Entry::check_target(target, 5).err().map(|e| e.to_string());
+
+        // Длина считается в символах, а не в байтах
+        assert_eq!(error("заряд"), None);
+        assert_eq!(error("заряд!"), Some("Слишком длинная задача".to_owned()));
+        assert_eq!(error(""), Some("Задача не может быть пустой".to_owned()));
+        for target in ["a\nb", "a\tb", "a\rb"] {
+            assert_eq!(
+                error(target),
+                Some("Задача не может содержать управляющие символы".to_owned()),
+                "{:?}",
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn backups_keep_exactly_n_copies_newest_first() {
+        let path = temp_path("rotate.txt");
+        let rotated = storage(&path).with_backups(3);
+        for hour in 1..=5 {
+            rotated
+                .save(entry(&format!("{}:00", hour), "задача").into())
+                .unwrap();
+        }
+
+        // Первое сохранение создает файл, копируются состояния до каждого следующего
+        assert_eq!(
+            rotated
+                .backups()
+                .iter()
+                .map(|(number, _)| *number)
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert!(!Path::new(&format!("{}.4", path)).exists());
+        let count = |number: usize| {
+            Storage::new(&format!("{}.{}", path, number))
+                .read()
+                .unwrap()
+                .len()
+        };
+        assert_eq!([count(1), count(2), count(3)], [4, 3, 2]);
+
+        // Без копий ничего не сдвигается
+        let path = temp_path("no_rotate.txt");
+        let plain = storage(&path).with_backups(0);
+        plain.save(entry("9:00", "a").into()).unwrap();
+        plain.save(entry("10:00", "b").into()).unwrap();
+        assert!(plain.backups().is_empty());
+        assert!(!Path::new(&format!("{}.1", path)).exists());
+    }
+
+    #[test]
+    fn swap_reorders_entries_at_the_same_time_only() {
+        let path = temp_path("swap.txt");
+        let storage = storage(&path);
+        for entry in [
+            entry("9:00", "а"),
+            entry("9:00", "б"),
+            entry("9:00", "в"),
+            entry("10:00", "г"),
+            Entry {
+                priority: 1,
+                ..entry("10:00", "д")
+            },
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+        let targets = || {
+            summary(&storage.read().unwrap())
+                .into_iter()
+                .map(|(_, target)| target)
+                .collect::<Vec<_>>()
+        };
+        let before = targets();
+        assert_eq!(&before[..3], ["а", "б", "в"]);
+
+        storage.swap(0, 2).unwrap();
+        assert_eq!(&targets()[..3], ["в", "б", "а"]);
+        storage.swap(1, 0).unwrap();
+        assert_eq!(&targets()[..3], ["б", "в", "а"]);
+
+        let error =
+            |first: usize, second: usize| storage.swap(first, second).unwrap_err().to_string();
+        assert_eq!(
+            error(0, 3),
+            "Поменять местами можно только записи на одно время."
+        );
+        assert_eq!(
+            error(3, 4),
+            "Записи с разным приоритетом упорядочены по приоритету."
+        );
+        assert_eq!(error(0, 9), "Записи с таким номером нет.");
+        assert_eq!(&targets()[..3], ["б", "в", "а"]);
+    }
+
+    #[test]
+    fn range_includes_both_bounds_and_may_be_empty() {
+        let path = temp_path("range.txt");
+        let storage = storage(&path);
+        for entry in [
+            entry("8:59", "до"),
+            entry("9:00", "начало"),
+            entry("12:00", "середина"),
+            entry("17:00", "конец"),
+            entry("17:01", "после"),
+            Entry {
+                time: None,
+                ..entry("0:00", "без времени")
+            },
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+        let range = |from: &str, to: &str| {
+            summary(
+                &storage
+                    .in_range(from.parse().unwrap(), to.parse().unwrap())
+                    .unwrap(),
+            )
+            .into_iter()
+            .map(|(_, target)| target)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(range("9:00", "17:00"), ["начало", "середина", "конец"]);
+        assert_eq!(range("12:00", "12:00"), ["середина"]);
+        assert!(range("13:00", "16:59").is_empty());
+        assert!(range("17:00", "9:00").is_empty());
+    }
+
+    #[test]
+    fn broken_blocks_are_skipped_by_line_or_fail_in_strict_mode() {
+        let text = "25:99\nсломано\n\n18:00\nужин\n\nxx\nтоже сломано\n\n7:05\nзарядка\n";
+        let mut skipped = Vec::new();
+        let list = Storage::parse_text(BufReader::new(text.as_bytes()), &mut skipped).unwrap();
+        assert_eq!(skipped, [1, 7]);
+        assert_eq!(list.len(), 2);
+
+        let path = temp_path("strict.txt");
+        write(&path, text).unwrap();
+        assert_eq!(storage(&path).read().unwrap().len(), 2);
+        assert_eq!(
+            storage(&path)
+                .with_strict(true)
+                .read()
+                .err()
+                .map(|e| e.to_string())
+                .as_deref(),
+            Some("Файл планера содержит некорректные записи.")
+        );
+
+        let mut skipped = Vec::new();
+        Storage::parse_text(BufReader::new("9:00\nзарядка\n".as_bytes()), &mut skipped).unwrap();
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn rollover_moves_only_unfinished_past_entries() {
+        let path = temp_path("rollover.txt");
+        let storage = storage(&path);
+        let dated = |date: &str, time: &str, target: &str| Entry {
+            date: date.to_owned(),
+            ..entry(time, target)
+        };
+        for entry in [
+            dated("2024-05-30", "9:00", "давнее"),
+            dated("2024-05-31", "10:00", "вчерашнее"),
+            Entry {
+                done: true,
+                ..dated("2024-05-31", "11:00", "выполнено")
+            },
+            Entry {
+                recurrence: Some(Recurrence::Daily),
+                ..dated("2024-05-31", "12:00", "повтор")
+            },
+            dated("", "13:00", "без даты"),
+            dated("2024-06-01", "14:00", "сегодня"),
+            dated("2024-06-02", "15:00", "завтра"),
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(storage.rollover(today).unwrap(), 2);
+        let dates = storage
+            .read()
+            .unwrap()
+            .iter()
+            .map(|entry| (entry.target().to_owned(), entry.date().to_owned()))
+            .collect::<BTreeMap<_, _>>();
+        for (target, date) in [
+            ("давнее", "2024-06-01"),
+            ("вчерашнее", "2024-06-01"),
+            ("выполнено", "2024-05-31"),
+            ("повтор", "2024-05-31"),
+            ("без даты", ""),
+            ("сегодня", "2024-06-01"),
+            ("завтра", "2024-06-02"),
+        ] {
+            assert_eq!(dates[target], date, "{}", target);
+        }
+
+        // Повторный перенос ничего не меняет
+        assert_eq!(storage.rollover(today).unwrap(), 0);
+    }
+
+    #[test]
+    fn archive_moves_done_entries_out_of_the_planner() {
+        let path = temp_path("archive_src.txt");
+        let archive_path = temp_path("archive.txt");
+        let archive = storage(&archive_path);
+        let storage = storage(&path);
+        archive
+            .save(
+                Entry {
+                    done: true,
+                    ..entry("7:00", "старое")
+                }
+                .into(),
+            )
+            .unwrap();
+        for entry in [
+            Entry {
+                done: true,
+                ..entry("9:00", "зарядка")
+            },
+            entry("12:00", "обед"),
+            Entry {
+                done: true,
+                ..entry("18:00", "ужин")
+            },
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+
+        assert_eq!(storage.archive(&archive).unwrap(), 2);
+        assert_eq!(
+            summary(&storage.read().unwrap()),
+            [(Some("12:00".to_owned()), "обед".to_owned())]
+        );
+        let archived = archive.read().unwrap();
+        assert_eq!(
+            summary(&archived),
+            [
+                (Some("7:00".to_owned()), "старое".to_owned()),
+                (Some("9:00".to_owned()), "зарядка".to_owned()),
+                (Some("18:00".to_owned()), "ужин".to_owned()),
+            ]
+        );
+        assert!(archived.iter().all(|entry| entry.done()));
+
+        // Без выполненных записей архив не меняется
+        assert_eq!(storage.archive(&archive).unwrap(), 0);
+        assert_eq!(archive.read().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn time_format_renders_each_variant() {
+        let format = |name: &str, time: &str| {
+            TimeFormat::from_name(name)
+                .unwrap()
+                .format(time.parse().unwrap())
+        };
+
+        assert_eq!(format("24", "9:05"), "9:05");
+        assert_eq!(format("12h", "0:05"), "12:05 AM");
+        assert_eq!(format("12", "12:30"), "12:30 PM");
+        assert_eq!(format("12", "23:59"), "11:59 PM");
+        assert_eq!(format("{hh}:{mm}", "9:05"), "09:05");
+        assert_eq!(format("{h}.{mm}", "9:05"), "9.05");
+        assert_eq!(format("{h}ч {m}м", "14:07"), "14ч 7м");
+
+        // Шаблон без часов или минут и с неизвестным полем не принимается
+        for name in [
+            "{h}",
+            "{mm}",
+            "{h}:{s}",
+            "{h}:{mm",
+            "{h}:{mm}}",
+            "как-нибудь",
+        ] {
+            assert!(TimeFormat::from_name(name).is_none(), "{}", name);
+        }
+        assert_eq!(
+            TimeFormat::from_setting("{h}").format("9:30".parse().unwrap()),
+            "9:30"
+        );
+    }
+
+    #[test]
+    fn ids_survive_sorting_and_deleting_other_entries() {
+        let path = temp_path("ids.txt");
+        let storage = storage(&path);
+        for (time, target) in [("12:00", "обед"), ("18:00", "ужин")] {
+            storage.save(entry(time, target).into()).unwrap();
+        }
+        let id_of = |target: &str| {
+            storage
+                .read()
+                .unwrap()
+                .iter()
+                .find(|entry| entry.target() == target)
+                .and_then(|entry| entry.id())
+                .unwrap()
+        };
+        let (lunch, dinner) = (id_of("обед"), id_of("ужин"));
+        assert_ne!(lunch, dinner);
+
+        // Более ранняя запись встает первой, а удаление сдвигает номера
+        storage.save(entry("7:00", "зарядка").into()).unwrap();
+        assert_eq!(storage.index_of(lunch).unwrap(), 1);
+        storage.delete(0).unwrap();
+        assert_eq!(storage.index_of(lunch).unwrap(), 0);
+        assert_eq!(storage.find_by_id(lunch).unwrap().unwrap().target(), "обед");
+        assert_eq!(
+            storage.find_by_id(dinner).unwrap().unwrap().target(),
+            "ужин"
+        );
+
+        // Новая запись не получает ID существующей
+        storage.save(entry("20:00", "чтение").into()).unwrap();
+        assert!(![lunch, dinner].contains(&id_of("чтение")));
+        assert!(storage.index_of(999).is_err());
+        assert!(storage.find_by_id(999).unwrap().is_none());
+    }
+
+    #[test]
+    fn compact_view_prints_one_line_per_entry() {
+        let list = boxed(&[
+            Entry {
+                duration_minutes: Some(90),
+                notes: "с собой\nвода".to_owned(),
+                ..entry("9:00", "спортзал")
+            },
+            Entry {
+                done: true,
+                label: Some("дом".to_owned()),
+                ..entry("12:30", "обед")
+            },
+            Entry {
+                time: None,
+                ..entry("0:00", "продукты")
+            },
+        ]);
+        let view = ListView::on(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()).with_compact(true);
+
+        let text = view.format_list(&list);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3, "{}", text);
+        assert_eq!(lines[0], "9:00–10:30  [ ] спортзал");
+        assert_eq!(lines[1], "12:30       [x] [дом] обед");
+        assert_eq!(lines[2], "весь день   [ ] продукты");
+        assert!(view.format_list(&[]).is_empty());
+    }
+
+    #[test]
+    fn only_dated_timed_entries_before_now_are_past() {
+        let lunch = entry("12:00", "обед");
+        assert!(lunch.is_past(at("12:01")));
+        assert!(!lunch.is_past(at("12:00")));
+        assert!(!lunch.is_past(at("11:59")));
+
+        let yesterday = Entry {
+            date: "2024-05-31".to_owned(),
+            ..entry("23:59", "вчера")
+        };
+        assert!(yesterday.is_past(at("0:00")));
+
+        let undated = Entry {
+            date: String::new(),
+            ..lunch.clone()
+        };
+        let untimed = Entry {
+            time: None,
+            ..lunch
+        };
+        assert!(!undated.is_past(at("23:59")));
+        assert!(!untimed.is_past(at("23:59")));
+    }
+
+    #[test]
+    fn logical_day_starts_at_the_configured_hour() {
+        let moment = |day: u32, time: &str| {
+            NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+        };
+        let date = |day: u32| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+
+        // При начале дня в 4:00 ночь до 4:00 относится к предыдущему дню
+        assert_eq!(logical_date(moment(2, "0:00"), 4), date(1));
+        assert_eq!(logical_date(moment(2, "3:59"), 4), date(1));
+        assert_eq!(logical_date(moment(2, "4:00"), 4), date(2));
+        assert_eq!(logical_date(moment(2, "23:59"), 4), date(2));
+        assert_eq!(
+            logical_date(moment(1, "0:00"), 4),
+            NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()
+        );
+
+        // По умолчанию день начинается в полночь
+        assert_eq!(logical_date(moment(2, "0:00"), 0), date(2));
+        assert_eq!(logical_date(moment(1, "23:59"), 0), date(1));
+    }
+
+    #[test]
+    fn unique_storage_skips_exact_duplicates_only() {
+        let path = temp_path("unique.txt");
+        let unique = storage(&path).with_unique(true);
+        unique.save(entry("9:00", "зарядка").into()).unwrap();
+        unique.save(entry("9:00", "зарядка").into()).unwrap();
+        unique.save(entry("9:00", "чтение").into()).unwrap();
+        unique.save(entry("10:00", "зарядка").into()).unwrap();
+        assert_eq!(unique.read().unwrap().len(), 3);
+
+        // Повтор внутри пакета тоже пропускается
+        let saved = unique
+            .save_all(boxed(&[
+                entry("9:00", "зарядка"),
+                entry("11:00", "обед"),
+                entry("11:00", "обед"),
+            ]))
+            .unwrap();
+        assert_eq!(saved, 1);
+        assert_eq!(unique.read().unwrap().len(), 4);
+
+        // Без флага повторы сохраняются как раньше
+        let path = temp_path("duplicates.txt");
+        let plain = storage(&path);
+        plain.save(entry("9:00", "зарядка").into()).unwrap();
+        plain.save(entry("9:00", "зарядка").into()).unwrap();
+        assert_eq!(plain.read().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn shift_parses_deltas_and_skips_entries_past_midnight() {
+        let delta = |delta: &str| ShiftModel::parse_delta(delta).ok();
+        assert_eq!(delta("+15"), Some(15));
+        assert_eq!(delta("-15"), Some(-15));
+        assert_eq!(delta("15"), Some(15));
+        assert_eq!(delta("+1:30"), Some(90));
+        assert_eq!(delta("-0:05"), Some(-5));
+        for invalid in ["", "+", "abc", "1:60", "+24:00", "1440", "--5"] {
+            assert_eq!(delta(invalid), None, "{:?}", invalid);
+        }
+
+        let path = temp_path("shift.txt");
+        let storage = storage(&path);
+        for entry in [
+            entry("0:10", "ночь"),
+            entry("12:00", "обед"),
+            entry("23:50", "сон"),
+            Entry {
+                time: None,
+                ..entry("0:00", "без времени")
+            },
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+        let times = || {
+            summary(&storage.read().unwrap())
+                .into_iter()
+                .map(|(time, target)| (time.unwrap_or_default(), target))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        // Время за 23:59 не переносится на следующий день
+        let (count, skipped) = storage.shift(15, None).unwrap();
+        assert_eq!((count, skipped), (2, vec!["сон".to_owned()]));
+        assert!(times().contains_key("0:25") && times().contains_key("12:15"));
+        assert!(times().contains_key("23:50"));
+
+        // И раньше 0:00 тоже
+        let (count, skipped) = storage.shift(-30, None).unwrap();
+        assert_eq!((count, skipped), (2, vec!["ночь".to_owned()]));
+        assert!(times().contains_key("0:25") && times().contains_key("11:45"));
+        assert!(times().contains_key("23:20"));
+    }
+
+    #[test]
+    fn json_save_writes_a_matching_text_mirror() {
+        for name in ["mirror.json", "mirror.jsonl"] {
+            let path = temp_path(name);
+            let mirror = Path::new(&path).with_extension("txt");
+            let _ = std::fs::remove_file(&mirror);
+            let storage = storage(&path).with_text_mirror(true);
+            storage.save(entry("18:00", "ужин").into()).unwrap();
+            storage
+                .save(
+                    Entry {
+                        category: "спорт".to_owned(),
+                        ..entry("7:00", "зарядка")
+                    }
+                    .into(),
+                )
+                .unwrap();
+
+            let text = Storage::new(&mirror.to_string_lossy()).read().unwrap();
+            let json = |list: &[Box<dyn EntryTrait>]| {
+                list.iter()
+                    .map(|entry| serde_json::Value::from(entry.as_ref()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(json(&text), json(&storage.read().unwrap()), "{}", name);
+            assert_eq!(text.len(), 2);
+        }
+
+        // Без настройки копия не пишется
+        let path = temp_path("no_mirror.json");
+        storage(&path).save(entry("9:00", "a").into()).unwrap();
+        assert!(!Path::new(&path).with_extension("txt").exists());
+    }
+
+    #[test]
+    fn label_round_trips_and_renders_as_a_badge() {
+        let labeled = |label: &str, time: &str, target: &str| Entry {
+            label: Some(label.to_owned()),
+            ..entry(time, target)
+        };
+        for name in ["labels.txt", "labels.json", "labels.md", "labels.yaml"] {
+            let path = temp_path(name);
+            let storage = storage(&path);
+            storage
+                .save(labeled("важно", "9:00", "отчёт").into())
+                .unwrap();
+            storage.save(entry("12:00", "обед").into()).unwrap();
+            let list = storage.read().unwrap();
+            assert_eq!(list[0].label(), Some("важно"), "{}", name);
+            assert_eq!(list[1].label(), None, "{}", name);
+        }
+
+        // Старая запись без метки читается без нее
+        let path = temp_path("legacy_label.txt");
+        write(&path, "9:00\nзарядка\n\n").unwrap();
+        assert_eq!(storage(&path).read().unwrap()[0].label(), None);
+
+        let list = boxed(&[
+            labeled("red", "9:00", "отчёт"),
+            labeled("важно", "10:00", "звонок"),
+        ]);
+        let view = |color: bool| {
+            ListView::on(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+                .with_color(color)
+                .with_compact(true)
+                .format_list(&list)
+        };
+        assert!(view(false).contains("[red] отчёт"));
+        assert!(view(false).contains("[важно] звонок"));
+        assert!(view(true).contains(&color::paint("[red]", color::RED)));
+        assert!(view(true).contains("[важно] звонок"));
+        assert_eq!(label_badge("важно", true), "[важно]");
+        assert!(ListView::format_entries(&list).contains("[важно]"));
+    }
+
+    #[test]
+    fn validate_reports_the_first_broken_line() {
+        let corrupted_line = |name: &str, content: &str| {
+            let path = temp_path(name);
+            write(&path, content).unwrap();
+            match storage(&path).validate() {
+                Ok(()) => None,
+                Err(e) => match e.downcast_ref() {
+                    Some(&AppError::Corrupted(line)) => Some(line),
+                    _ => panic!("{}: {}", name, e),
+                },
+            }
+        };
+
+        assert_eq!(
+            corrupted_line("valid.txt", "9:00\nзарядка\n\n18:00\nужин\n"),
+            None
+        );
+        assert_eq!(corrupted_line("valid.json", "[]"), None);
+        assert_eq!(corrupted_line("empty.json", ""), None);
+        assert_eq!(
+            corrupted_line(
+                "broken.txt",
+                "9:00\nзарядка\n\n25:99\nсломано\n\nxx\nтоже\n"
+            ),
+            Some(4)
+        );
+        assert_eq!(
+            corrupted_line(
+                "broken.jsonl",
+                "{\"time\": \"9:00\", \"target\": \"a\"}\nне json\n"
+            ),
+            Some(2)
+        );
+        assert_eq!(
+            corrupted_line("broken.json", "[\n{\"target\": \"a\"},\n{\n"),
+            Some(4)
+        );
+
+        // Отсутствующий файл - пустой планер
+        assert!(storage(&temp_path("missing.txt")).validate().is_ok());
+        assert_eq!(
+            AppError::Corrupted(4).to_string(),
+            "Файл планера повреждён в строке 4"
+        );
+    }
+
+    #[test]
+    fn crowded_warning_appears_only_above_threshold() {
+        let day = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let view = ListView::on(day);
+        assert_eq!(view.crowded_warning(9, 10), None);
+        assert_eq!(view.crowded_warning(10, 10), None);
+        assert_eq!(
+            view.crowded_warning(11, 10).as_deref(),
+            Some("⚠ Много задач на 2024-06-01 (11)")
+        );
+        assert_eq!(
+            ListView::today().crowded_warning(11, 10).as_deref(),
+            Some("⚠ Много задач на сегодня (11)")
+        );
+        assert_eq!(
+            ListView::tomorrow().crowded_warning(4, 3).as_deref(),
+            Some("⚠ Много задач на завтра (4)")
+        );
+
+        // Список из нескольких дней не предупреждает
+        assert_eq!(ListView::default().crowded_warning(100, 10), None);
+    }
+
+    #[test]
+    fn merge_joins_two_entries_and_rejects_bad_pairs() {
+        let path = temp_path("merge.txt");
+        let storage = storage(&path);
+        for entry in [
+            Entry {
+                notes: "взять паспорт".to_owned(),
+                ..entry("11:00", "банк")
+            },
+            entry("9:30", "почта"),
+            Entry {
+                notes: "список".to_owned(),
+                ..entry("15:00", "магазин")
+            },
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+
+        // Записи: 0 - почта 9:30, 1 - банк 11:00, 2 - магазин 15:00
+        storage.merge(1, 0).unwrap();
+        let list = storage.read().unwrap();
+        assert_eq!(
+            summary(&list),
+            [
+                (Some("9:30".to_owned()), "банк + почта".to_owned()),
+                (Some("15:00".to_owned()), "магазин".to_owned()),
+            ]
+        );
+        assert_eq!(list[0].notes(), "взять паспорт");
+
+        storage.merge(0, 1).unwrap();
+        let list = storage.read().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].target(), "банк + почта + магазин");
+        assert_eq!(list[0].notes(), "взять паспорт\nсписок");
+
+        let error =
+            |first: usize, second: usize| storage.merge(first, second).unwrap_err().to_string();
+        assert_eq!(error(0, 0), "Нельзя объединить запись саму с собой.");
+        assert_eq!(error(0, 5), "Записи с таким номером нет.");
+
+        // Объединенная задача проверяется, как и введенная
+        storage
+            .save(entry("20:00", &"я".repeat(190)).into())
+            .unwrap();
+        assert_eq!(error(0, 1), "Слишком длинная задача");
+        assert_eq!(storage.read().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn recent_targets_are_unique_and_newest_first() {
+        let path = temp_path("recent.txt");
+        let storage = storage(&path);
+        let created = |seconds: u64, time: &str, target: &str| Entry {
+            created_at: Some(seconds),
+            ..entry(time, target)
+        };
+        for entry in [
+            entry("6:00", "старая без времени добавления"),
+            created(100, "7:00", "зарядка"),
+            created(300, "8:00", "Зарядка "),
+            created(200, "9:00", "почта"),
+            created(400, "10:00", "обед"),
+            created(250, "11:00", "звонок"),
+        ] {
+            storage.save(entry.into()).unwrap();
+        }
+
+        // Повтор "зарядка" засчитывается по самому новому добавлению
+        assert_eq!(
+            storage.recent_targets(10).unwrap(),
+            [
+                "обед",
+                "Зарядка",
+                "звонок",
+                "почта",
+                "старая без времени добавления"
+            ]
+        );
+        assert_eq!(storage.recent_targets(2).unwrap(), ["обед", "Зарядка"]);
+        assert!(storage.recent_targets(0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::OnceLock;
+
+/// Уровень подробности журнала
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    #[default]
+    Off,
+    Debug,
+}
+
+/// Уровень журнала из переменной окружения MY_PLANNER_LOG, по умолчанию
+/// журнал выключен. Переменная читается один раз за запуск
+pub fn level() -> Level {
+    static LEVEL: OnceLock<Level> = OnceLock::new();
+
+    *LEVEL.get_or_init(|| match std::env::var("MY_PLANNER_LOG") {
+        Ok(name) if name.trim().eq_ignore_ascii_case("debug") => Level::Debug,
+        _ => Level::Off,
+    })
+}
+
+/// Проверка, выводятся ли сообщения уровня `level`
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level <= self::level()
+}
+
+/// Вывод отладочного сообщения в stderr при MY_PLANNER_LOG=debug
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Debug) {
+            eprintln!("[debug] {}", format_args!($($arg)*));
+        }
+    };
+}
@@ -0,0 +1,363 @@
+use crate::{format_time, tr, AppError, EditEntryModel, EntryTrait, ModelTrait, Result, CONTAINER};
+use std::{
+    io::{stdin, stdout, Read, Write},
+    ops::Range,
+    process::{Command, Stdio},
+};
+
+/// Положение выделенной записи и прокрутки списка, не зависит от терминала
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    index: usize,
+    offset: usize,
+    len: usize,
+    height: usize,
+}
+
+impl Selection {
+    /// Выделение первой записи списка из `len` записей, на экране
+    /// помещается `height` строк
+    pub fn new(len: usize, height: usize) -> Self {
+        Self {
+            index: 0,
+            offset: 0,
+            len,
+            height: height.max(1),
+        }
+    }
+
+    /// Индекс выделенной записи, в пустом списке выделения нет
+    pub fn index(&self) -> Option<usize> {
+        (self.len > 0).then_some(self.index)
+    }
+
+    /// Переход к предыдущей записи, на первой записи выделение остается
+    pub fn up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+        self.scroll();
+    }
+
+    /// Переход к следующей записи, на последней записи выделение остается
+    pub fn down(&mut self) {
+        if self.index + 1 < self.len {
+            self.index += 1;
+        }
+        self.scroll();
+    }
+
+    /// Новый размер списка после удаления или изменения записи,
+    /// выделение остается в пределах списка
+    pub fn resize(&mut self, len: usize) {
+        self.len = len;
+        self.index = self.index.min(len.saturating_sub(1));
+        self.scroll();
+    }
+
+    /// Индексы записей, которые видны на экране
+    pub fn visible(&self) -> Range<usize> {
+        self.offset..(self.offset + self.height).min(self.len)
+    }
+
+    /// Прокрутка так, чтобы выделенная запись была на экране
+    fn scroll(&mut self) {
+        if self.index < self.offset {
+            self.offset = self.index;
+        } else if self.index >= self.offset + self.height {
+            self.offset = self.index + 1 - self.height;
+        }
+        self.offset = self.offset.min(self.len.saturating_sub(self.height));
+    }
+}
+
+/// Нажатая клавиша
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Enter,
+    Quit,
+    Char(char),
+}
+
+/// Чтение одной клавиши, стрелки приходят последовательностями `ESC [ A`
+/// и `ESC [ B`, а Ctrl-C, `q` и одиночный Esc завершают работу
+fn read_key() -> Result<Key> {
+    let mut byte = [0u8; 1];
+    stdin().read_exact(&mut byte)?;
+    if byte[0] != 0x1b {
+        return Ok(decode_key(byte[0], &[]));
+    }
+
+    // Продолжение последовательности ожидается не дольше 0,1 секунды,
+    // иначе одиночный Esc ждал бы следующих нажатий
+    stty(&["min", "0", "time", "1"])?;
+    let mut sequence = [0u8; 2];
+    let read = read_available(&mut sequence);
+    stty(&["min", "1", "time", "0"])?;
+
+    Ok(decode_key(byte[0], &sequence[..read?]))
+}
+
+/// Чтение до заполнения буфера или до истечения ожидания терминала,
+/// возвращается число прочитанных байт
+fn read_available(buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match stdin().read(&mut buf[read..])? {
+            0 => break,
+            count => read += count,
+        }
+    }
+
+    Ok(read)
+}
+
+/// Клавиша по первому байту и байтам, пришедшим следом за Esc
+fn decode_key(byte: u8, sequence: &[u8]) -> Key {
+    match byte {
+        0x1b => match sequence {
+            [] => Key::Quit,
+            [b'[', b'A'] => Key::Up,
+            [b'[', b'B'] => Key::Down,
+            _ => Key::Char('\x1b'),
+        },
+        b'\r' | b'\n' => Key::Enter,
+        3 | b'q' => Key::Quit,
+        b'k' => Key::Up,
+        b'j' => Key::Down,
+        byte => Key::Char(byte as char),
+    }
+}
+
+/// Режим терминала без построчного ввода и эха, прежние настройки
+/// восстанавливаются при удалении, в том числе при ошибке
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        let output = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()?;
+        if !output.status.success() {
+            Err(AppError::Msg("Этот режим работает только в терминале."))?
+        }
+
+        let saved = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?25l");
+        stdout().flush()?;
+
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+        print!("\x1b[?25h");
+        let _ = stdout().flush();
+    }
+}
+
+/// Изменение настроек терминала командой stty
+fn stty(args: &[&str]) -> Result<()> {
+    let status = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()?;
+    if !status.success() {
+        Err(AppError::Msg("Не удалось изменить режим терминала."))?
+    }
+
+    Ok(())
+}
+
+/// Число строк терминала для списка, без заголовка и подсказки,
+/// если размер терминала неизвестен - 20 строк
+fn list_height() -> usize {
+    Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .and_then(|output| {
+            let size = String::from_utf8_lossy(&output.stdout).into_owned();
+            size.split_whitespace().next()?.parse::<usize>().ok()
+        })
+        .filter(|rows| *rows > 4)
+        .map_or(20, |rows| rows - 4)
+}
+
+/// Одна строка списка: дата, время, отметка и задача
+fn format_line(entry: &dyn EntryTrait) -> String {
+    let time = entry
+        .time()
+        .map_or_else(|| tr("весь день").to_owned(), format_time);
+
+    format!(
+        "{} {:>9} [{}] {}",
+        entry.date(),
+        time,
+        if entry.done() { "x" } else { " " },
+        entry.target()
+    )
+}
+
+/// Отрисовка списка с выделенной записью, в режиме raw строки
+/// завершаются `\r\n`
+fn render(list: &[Box<dyn EntryTrait>], selection: &Selection, hint: &str) -> Result<()> {
+    let mut output = String::from("\x1b[2J\x1b[H");
+    output += &format!("{}\r\n\r\n", tr("Мое расписание:"));
+
+    if list.is_empty() {
+        output += &format!("{}\r\n", tr("Расписание пусто. Добавь первую задачу!"));
+    }
+    for index in selection.visible() {
+        let line = format_line(list[index].as_ref());
+        if selection.index() == Some(index) {
+            output += &format!("\x1b[7m{}\x1b[0m\r\n", line);
+        } else {
+            output += &format!("{}\r\n", line);
+        }
+    }
+    output += &format!("\r\n{}", hint);
+
+    print!("{}", output);
+    stdout().flush()?;
+
+    Ok(())
+}
+
+/// Модель списка записей в терминале: стрелки перемещают выделение,
+/// Enter открывает действия с записью
+#[derive(Default)]
+pub struct TuiModel;
+
+impl ModelTrait for TuiModel {
+    fn exec(&self) -> Result<()> {
+        let storage = CONTAINER.get().unwrap().storage();
+        let mut list = storage.read()?;
+        let mut selection = Selection::new(list.len(), list_height());
+        let mut raw = RawMode::enable()?;
+
+        loop {
+            render(
+                &list,
+                &selection,
+                tr("↑/↓ - выбор, Enter - действия, q - выход"),
+            )?;
+
+            match read_key()? {
+                Key::Up => selection.up(),
+                Key::Down => selection.down(),
+                Key::Quit => break,
+                Key::Enter => {
+                    let Some(index) = selection.index() else {
+                        continue;
+                    };
+                    render(
+                        &list,
+                        &selection,
+                        tr("e - изменить, d - удалить, другая клавиша - назад"),
+                    )?;
+
+                    match read_key()? {
+                        Key::Char('e') => {
+                            // Вопросы редактирования задаются в обычном режиме терминала
+                            drop(raw);
+                            print!("\x1b[2J\x1b[H");
+                            EditEntryModel::edit(index)?;
+                            raw = RawMode::enable()?;
+                        }
+                        Key::Char('d') => storage.delete(index)?,
+                        _ => {}
+                    }
+
+                    list = storage.read()?;
+                    selection.resize(list.len());
+                }
+                Key::Char(_) => {}
+            }
+        }
+
+        drop(raw);
+        print!("\x1b[2J\x1b[H");
+        stdout().flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_stays_within_list() {
+        let mut selection = Selection::new(3, 10);
+        assert_eq!(selection.index(), Some(0));
+        selection.up();
+        assert_eq!(selection.index(), Some(0));
+        selection.down();
+        selection.down();
+        selection.down();
+        assert_eq!(selection.index(), Some(2));
+        assert_eq!(selection.visible(), 0..3);
+    }
+
+    #[test]
+    fn selection_scrolls_to_keep_index_visible() {
+        let mut selection = Selection::new(10, 3);
+        for _ in 0..4 {
+            selection.down();
+        }
+        assert_eq!(selection.index(), Some(4));
+        assert_eq!(selection.visible(), 2..5);
+
+        for _ in 0..3 {
+            selection.up();
+        }
+        assert_eq!(selection.index(), Some(1));
+        assert_eq!(selection.visible(), 1..4);
+    }
+
+    #[test]
+    fn selection_follows_resized_list() {
+        let mut selection = Selection::new(10, 3);
+        for _ in 0..9 {
+            selection.down();
+        }
+        assert_eq!(selection.visible(), 7..10);
+
+        selection.resize(5);
+        assert_eq!(selection.index(), Some(4));
+        assert_eq!(selection.visible(), 2..5);
+
+        selection.resize(0);
+        assert_eq!(selection.index(), None);
+        assert_eq!(selection.visible(), 0..0);
+        selection.down();
+        assert_eq!(selection.index(), None);
+    }
+
+    #[test]
+    fn zero_height_shows_one_line() {
+        let mut selection = Selection::new(2, 0);
+        selection.down();
+        assert_eq!(selection.visible(), 1..2);
+    }
+
+    #[test]
+    fn lone_escape_quits_and_arrows_move() {
+        assert_eq!(decode_key(0x1b, &[]), Key::Quit);
+        assert_eq!(decode_key(0x1b, b"[A"), Key::Up);
+        assert_eq!(decode_key(0x1b, b"[B"), Key::Down);
+        assert_eq!(decode_key(0x1b, b"["), Key::Char('\x1b'));
+        assert_eq!(decode_key(b'q', &[]), Key::Quit);
+        assert_eq!(decode_key(b'\r', &[]), Key::Enter);
+        assert_eq!(decode_key(b'e', &[]), Key::Char('e'));
+    }
+}
@@ -0,0 +1,36 @@
+use my_planner::{App, Storage};
+use std::{fs::create_dir_all, process::ExitCode};
+
+/// Команды выполняются в одном процессе с общим контейнером настроек,
+/// поэтому весь сценарий - один тест с файлом из `--file`
+#[test]
+fn app_runs_commands_on_file_from_flag() {
+    let dir = std::env::temp_dir().join(format!("my_planner_app_{}", std::process::id()));
+    create_dir_all(&dir).unwrap();
+    let path = dir.join("planner.txt").to_string_lossy().into_owned();
+    std::fs::write(&path, "9:00\nзарядка\ndate: 2024-06-01\n\n").unwrap();
+
+    let run = |args: &[&str]| {
+        let mut full = vec!["--quiet".to_owned(), "--file".to_owned(), path.clone()];
+        full.extend(args.iter().map(|arg| arg.to_string()));
+        App::run(full)
+    };
+
+    assert_eq!(run(&["copy", "1", "18:30"]), ExitCode::SUCCESS);
+    assert_eq!(run(&["move", "1", "7:15"]), ExitCode::SUCCESS);
+    assert_eq!(run(&["copy", "5", "10:00"]), ExitCode::from(1));
+    assert_eq!(run(&["frobnicate"]), ExitCode::from(1));
+
+    let list = Storage::new(&path).read().unwrap();
+    let times = list
+        .iter()
+        .map(|entry| (entry.time().unwrap().to_string(), entry.target().to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        times,
+        [
+            ("7:15".to_owned(), "зарядка".to_owned()),
+            ("18:30".to_owned(), "зарядка".to_owned()),
+        ]
+    );
+}
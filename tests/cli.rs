@@ -0,0 +1,728 @@
+use my_planner::Storage;
+use std::{
+    fs::create_dir_all,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+/// Отдельный каталог теста, он же домашний и текущий каталог программы,
+/// чтобы не читать чужой config.toml
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("my_planner_cli_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Запуск программы с вводом из конвейера и файлом планера `planner.txt`
+fn run(dir: &PathBuf, args: &[&str], input: &str) -> Output {
+    let mut full = vec!["--file", "planner.txt"];
+    full.extend(args);
+    spawn(dir, &full, input, &[])
+}
+
+/// Запуск программы без выбора файла и с дополнительными переменными
+/// окружения, профили хранятся в `data` каталога теста
+fn spawn(dir: &PathBuf, args: &[&str], input: &str, envs: &[(&str, &str)]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_my-planner"))
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env_remove("MY_PLANNER_PATH")
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Команда может завершиться, не дочитав ввод, тогда запись в закрытый
+    // канал не ошибка теста
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn add_reads_piped_input_as_batch() {
+    let dir = temp_dir("piped");
+    let output = run(&dir, &["add"], "9:30\nзарядка\n\n10:00\nчтение\n");
+    assert!(output.status.success());
+
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    let targets = list.iter().map(|entry| entry.target()).collect::<Vec<_>>();
+    assert_eq!(targets, ["зарядка", "чтение"]);
+}
+
+#[test]
+fn english_help_is_fully_translated() {
+    let dir = temp_dir("help");
+    let output = run(&dir, &["--lang", "en", "--help"], "");
+    assert!(output.status.success());
+
+    let help = String::from_utf8(output.stdout).unwrap();
+    let untranslated = help
+        .lines()
+        .filter(|line| {
+            line.chars()
+                .any(|c| matches!(c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё'))
+        })
+        .collect::<Vec<_>>();
+    assert!(untranslated.is_empty(), "{:?}", untranslated);
+}
+
+#[test]
+fn quiet_commands_print_nothing() {
+    let dir = temp_dir("quiet");
+    std::fs::write(dir.join("other.txt"), "10:00\nчтение\n\n").unwrap();
+    let commands = [
+        (&["add", "--batch"][..], "9:30\nзарядка\n"),
+        (&["import", "other.txt"], ""),
+        (&["recategorize", "Общее", "дом"], ""),
+        (&["rollover"], ""),
+        (&["shift", "+10"], ""),
+        (&["archive"], ""),
+    ];
+    for (args, input) in commands {
+        let mut full = vec!["--quiet"];
+        full.extend(args);
+        let output = run(&dir, &full, input);
+        assert!(output.status.success(), "{:?}", args);
+        assert!(
+            output.stdout.is_empty(),
+            "{:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 2);
+}
+
+#[test]
+fn reversed_json_list_is_reverse_of_default() {
+    let dir = temp_dir("reverse");
+    let output = run(
+        &dir,
+        &["add", "--batch"],
+        "9:00\nзарядка\n12:00\nобед\n18:00\nужин\n",
+    );
+    assert!(output.status.success());
+
+    let targets = |args: &[&str]| {
+        let output = run(&dir, args, "");
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["target"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    let default = targets(&["--quiet", "list", "--all", "--json"]);
+    let mut reversed = targets(&["--quiet", "list", "--all", "--json", "--reverse"]);
+    assert_eq!(default, ["зарядка", "обед", "ужин"]);
+    reversed.reverse();
+    assert_eq!(reversed, default);
+}
+
+#[test]
+fn profiles_keep_separate_files_created_on_first_write() {
+    let dir = temp_dir("profiles");
+    let profiles = dir.join("data/my_planner");
+
+    // Справка, where и пробный запуск не создают каталог профилей
+    for args in [
+        &["--profile", "work", "--help"][..],
+        &["--profile", "work", "where"],
+        &["--profile", "work", "--dry-run", "add", "--batch"],
+    ] {
+        spawn(&dir, args, "9:00\nотчёт\n", &[]);
+        assert!(!profiles.exists(), "{:?}", args);
+    }
+
+    let output = spawn(
+        &dir,
+        &["--profile", "work", "add", "--batch"],
+        "9:00\nотчёт\n",
+        &[],
+    );
+    assert!(output.status.success());
+    let output = spawn(
+        &dir,
+        &["--profile", "home", "add", "--batch"],
+        "19:00\nужин\n",
+        &[],
+    );
+    assert!(output.status.success());
+
+    let targets = |name: &str| {
+        Storage::new(&profiles.join(name).to_string_lossy())
+            .read()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.target().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(targets("work.txt"), ["отчёт"]);
+    assert_eq!(targets("home.txt"), ["ужин"]);
+}
+
+#[test]
+fn menu_add_asks_questions_even_with_piped_input() {
+    let dir = temp_dir("menu");
+    let input = "1\nзарядка\n2099-01-01\n9:00\n\n\n\n\n\n\n\n\nвыход\n4\n";
+    let output = run(&dir, &[], input);
+    assert!(output.status.success());
+
+    // После добавления меню показывается снова и получает свой пункт "4"
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("4. Выход").count(), 2);
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].date(), "2099-01-01");
+}
+
+#[test]
+fn menu_add_asks_again_for_invalid_category() {
+    let dir = temp_dir("category");
+    let input = "1\nзарядка\n2099-01-01\n9:00\n\n\n\nдом\tсад\nдом\n\n\n\n\nвыход\n4\n";
+    let output = run(&dir, &[], input);
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Категория не может содержать управляющие символы"));
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].category(), "дом");
+}
+
+#[test]
+fn local_config_wins_over_home_config_and_environment_over_both() {
+    let dir = temp_dir("config");
+    let home_config = dir.join(".config/my_planner");
+    create_dir_all(&home_config).unwrap();
+    std::fs::write(
+        home_config.join("config.toml"),
+        "storage_path = \"home.txt\"\n",
+    )
+    .unwrap();
+
+    let path = |envs: &[(&str, &str)]| {
+        let output = spawn(&dir, &["--quiet", "where"], "", envs);
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert!(path(&[]).starts_with(&format!("{}\n", dir.join("home.txt").display())));
+
+    std::fs::write(dir.join("config.toml"), "storage_path = \"local.txt\"\n").unwrap();
+    assert!(path(&[]).starts_with(&format!("{}\n", dir.join("local.txt").display())));
+    assert!(path(&[("MY_PLANNER_PATH", "env.txt")])
+        .starts_with(&format!("{}\n", dir.join("env.txt").display())));
+}
+
+#[test]
+fn blank_target_is_asked_again_and_end_of_input_exits() {
+    let dir = temp_dir("blank");
+    let input = "1\n   \n\nзарядка\n2099-01-01\n9:00\n\n\n\n\n\n\n\n\nвыход\n4\n";
+    let output = run(&dir, &[], input);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Два пустых ответа, задача и вопрос о следующей записи перед "выход"
+    assert_eq!(stdout.matches("Что планируешь делать?").count(), 5);
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].target(), "зарядка");
+
+    // Конец ввода на вопросе о задаче завершает программу без ошибки
+    let dir = temp_dir("eof");
+    let output = run(&dir, &[], "1\n");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().is_empty());
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert!(list.is_empty());
+}
+
+#[test]
+fn end_of_input_on_any_entry_prompt_exits_cleanly() {
+    // Ввод обрывается после задачи, даты, времени, приоритета и категории
+    let answers = ["1", "зарядка", "2099-01-01", "9:00", "", "", "", ""];
+    for cut in 2..=answers.len() {
+        let dir = temp_dir(&format!("eof_{}", cut));
+        let input = answers[..cut].join("\n") + "\n";
+        let output = run(&dir, &[], &input);
+        assert!(output.status.success(), "ввод: {:?}", input);
+        assert!(String::from_utf8(output.stderr).unwrap().is_empty());
+
+        let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+            .read()
+            .unwrap();
+        assert!(list.is_empty(), "ввод: {:?}", input);
+    }
+}
+
+#[test]
+fn move_changes_time_and_rejects_bad_number_or_time() {
+    let dir = temp_dir("move");
+    let output = run(&dir, &["add"], "9:30\nзарядка\n\n10:00\nчтение\n");
+    assert!(output.status.success());
+
+    let output = run(&dir, &["move", "1", "11:15"], "");
+    assert!(output.status.success());
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    let entries = list
+        .iter()
+        .map(|entry| (entry.time().unwrap().to_string(), entry.target()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("10:00".to_owned(), "чтение"),
+            ("11:15".to_owned(), "зарядка")
+        ]
+    );
+
+    for (args, error) in [
+        (["move", "3", "12:00"], "Записи с таким номером нет."),
+        (["move", "1", "25:00"], "Неверное время."),
+    ] {
+        let output = run(&dir, &args, "");
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains(error));
+    }
+    let after = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(after.len(), 2);
+    assert!(after.iter().zip(&list).all(|(a, b)| a.cmp(b).is_eq()));
+}
+
+#[test]
+fn version_prints_package_version() {
+    let dir = temp_dir("version");
+    let output = run(&dir, &["--version"], "");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("my-planner {}\n", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn tomorrow_entries_show_only_in_tomorrow_view() {
+    let dir = temp_dir("tomorrow");
+    let output = run(&dir, &["add", "--tomorrow"], "9:00\nотчёт\n");
+    assert!(output.status.success());
+
+    let stdout = |args: &[&str]| String::from_utf8(run(&dir, args, "").stdout).unwrap();
+    assert!(stdout(&["tomorrow"]).contains("отчёт"));
+    assert!(!stdout(&["list"]).contains("отчёт"));
+}
+
+#[test]
+fn sort_modes_order_the_same_file_differently() {
+    let dir = temp_dir("sort");
+    std::fs::write(
+        dir.join("planner.json"),
+        r#"[
+            {"date": "2099-01-01", "time": "12:00", "target": "обед", "created_at": 200},
+            {"date": "2099-01-01", "time": "9:00", "target": "зарядка", "created_at": 100},
+            {"date": "2099-01-01", "time": "18:00", "target": "ужин", "created_at": 300}
+        ]"#,
+    )
+    .unwrap();
+
+    let targets = |mode: &str| {
+        let args = [
+            "--file",
+            "planner.json",
+            "--sort",
+            mode,
+            "list",
+            "--all",
+            "--json",
+        ];
+        let output = spawn(&dir, &args, "", &[]);
+        assert!(output.status.success(), "{}", mode);
+        let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        list.as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["target"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(targets("time"), ["зарядка", "обед", "ужин"]);
+    assert_eq!(targets("insertion"), ["обед", "зарядка", "ужин"]);
+    assert_eq!(targets("created"), ["ужин", "обед", "зарядка"]);
+}
+
+#[test]
+fn skipped_lines_are_reported_once_and_strict_fails() {
+    let dir = temp_dir("skipped");
+    std::fs::write(
+        dir.join("planner.txt"),
+        "25:99\nсломано\n\n18:00\nужин\n\nxx\nтоже сломано\n\n",
+    )
+    .unwrap();
+
+    // Без проверки файла при запуске неразобранные записи только пропускаются
+    let output = run(&dir, &["--skip-validation", "list", "--all"], "");
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr
+            .matches("Пропущено некорректных записей: 2 (строки 1, 7)")
+            .count(),
+        1,
+        "{}",
+        stderr
+    );
+    assert!(String::from_utf8(output.stdout).unwrap().contains("ужин"));
+
+    let output = run(
+        &dir,
+        &["--skip-validation", "--strict", "list", "--all"],
+        "",
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Файл планера содержит некорректные записи."));
+}
+
+#[test]
+fn debug_log_goes_to_stderr_only_when_enabled() {
+    let dir = temp_dir("log");
+    let args = ["--file", "planner.txt", "add", "--batch"];
+    let output = spawn(
+        &dir,
+        &args,
+        "9:00\nзарядка\n",
+        &[("MY_PLANNER_LOG", "debug")],
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for expected in [
+        "[debug] блокировка получена: planner.txt.lock",
+        "[debug] записано записей в planner.txt: 1",
+    ] {
+        assert!(stderr.lines().any(|line| line == expected), "{}", stderr);
+    }
+    assert!(!String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("[debug]"));
+
+    let output = spawn(&dir, &args, "10:00\nчтение\n", &[]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[debug]"));
+}
+
+#[test]
+fn busy_time_can_be_changed_or_kept() {
+    let dir = temp_dir("conflict");
+    // Приоритет, длительность, напоминание, категория, метка, повтор и заметки
+    let fields = "\n\n\n\n\n\n\n";
+    let input = format!(
+        "1\nзарядка\n2099-01-01\n9:00\n{f}чтение\n2099-01-01\n9:00\n{f}да\n10:00\n\
+         прогулка\n2099-01-01\n9:00\n{f}нет\nвыход\n4\n",
+        f = fields
+    );
+    let output = run(&dir, &[], &input);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout.matches("Время занято. Ввести другое время?").count(),
+        2
+    );
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    let entries = list
+        .iter()
+        .map(|entry| (entry.time().unwrap().to_string(), entry.target()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("9:00".to_owned(), "зарядка"),
+            ("9:00".to_owned(), "прогулка"),
+            ("10:00".to_owned(), "чтение"),
+        ]
+    );
+}
+
+#[test]
+fn entries_added_in_sequence_sort_newest_first() {
+    let dir = temp_dir("created");
+    let add = |input: &str| assert!(run(&dir, &["add", "--batch"], input).status.success());
+    add("7:00\nзарядка\n");
+    // Время добавления хранится в секундах
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    add("20:00\nчтение\n");
+    let path = dir.join("planner.txt");
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("{}12:00\nстарая запись\n\n", text)).unwrap();
+
+    let output = run(&dir, &["--sort", "created", "list", "--all", "--json"], "");
+    assert!(output.status.success());
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let targets = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["target"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(targets, ["чтение", "зарядка", "старая запись"]);
+    assert!(list[0]["created_at"].as_u64() > list[1]["created_at"].as_u64());
+    assert!(list[2]["created_at"].is_null());
+}
+
+#[test]
+fn multi_add_saves_three_targets_at_one_time() {
+    let dir = temp_dir("multi");
+    // Слишком длинная задача пропускается, остальные сохраняются
+    let input = format!("9:00\nпочта\n{}\nзвонок\nкофе\n\n", "я".repeat(201));
+    let output = run(&dir, &["add", "--multi"], &input);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Добавлено записей: 3"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Слишком длинная задача"));
+
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 3);
+    assert!(list
+        .iter()
+        .all(|entry| entry.time().map(|time| time.to_string()).as_deref() == Some("9:00")));
+    let mut targets = list.iter().map(|entry| entry.target()).collect::<Vec<_>>();
+    targets.sort();
+    assert_eq!(targets, ["звонок", "кофе", "почта"]);
+}
+
+#[test]
+fn two_slot_template_fills_a_week_with_fourteen_entries() {
+    let dir = temp_dir("template");
+    create_dir_all(dir.join("templates")).unwrap();
+    std::fs::write(
+        dir.join("templates/week.txt"),
+        "# будни и выходные\n8:00 зарядка\n\n9:00 работа\n",
+    )
+    .unwrap();
+    // Среда 5 июня 2024, одна запись шаблона уже есть
+    std::fs::write(
+        dir.join("planner.txt"),
+        "8:00\nзарядка\ndate: 2024-06-05\n\n",
+    )
+    .unwrap();
+
+    let output = run(&dir, &["apply-template", "week", "2024-06-05"], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Добавлено записей: 13"));
+
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 14);
+    assert_eq!(list.first().unwrap().date(), "2024-06-03");
+    assert_eq!(list.last().unwrap().date(), "2024-06-09");
+    for day in 3..=9 {
+        let date = format!("2024-06-{:02}", day);
+        let targets = list
+            .iter()
+            .filter(|entry| entry.date() == date)
+            .map(|entry| entry.target())
+            .collect::<Vec<_>>();
+        assert_eq!(targets, ["зарядка", "работа"], "{}", date);
+    }
+
+    let output = run(&dir, &["apply-template", "week", "2024-06-05"], "");
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Добавлено записей: 0"));
+}
+
+#[test]
+fn output_file_matches_stdout() {
+    let dir = temp_dir("output");
+    assert!(run(&dir, &["add"], "9:30\nзарядка\n\n18:00\nужин\n")
+        .status
+        .success());
+
+    for args in [&["list", "--all"][..], &["list", "--all", "--json"][..]] {
+        let printed = run(&dir, args, "");
+        assert!(printed.status.success());
+        let mut with_output = args.to_vec();
+        with_output.extend(["--output", "schedule.txt"]);
+        let written = run(&dir, &with_output, "");
+        assert!(written.status.success());
+        assert!(written.stdout.is_empty());
+
+        let file = std::fs::read_to_string(dir.join("schedule.txt")).unwrap();
+        assert_eq!(
+            file,
+            String::from_utf8(printed.stdout).unwrap(),
+            "{:?}",
+            args
+        );
+    }
+
+    // Ошибка записи показывается понятным сообщением
+    let output = run(&dir, &["list", "--output", "нет/такого/каталога.txt"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Не удалось записать файл."));
+}
+
+#[test]
+fn if_not_exists_skips_a_repeated_add() {
+    let dir = temp_dir("if_not_exists");
+    let args = ["add", "--batch", "--if-not-exists"];
+    assert!(run(&dir, &args, "9:00\nзарядка\n").status.success());
+    let output = run(&dir, &args, "9:00\nзарядка\n");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Уже существует, пропущено"));
+
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 1);
+}
+
+#[test]
+fn where_names_the_path_and_its_source() {
+    let dir = temp_dir("where");
+    let path = |args: &[&str], envs: &[(&str, &str)]| {
+        let mut full = args.to_vec();
+        full.push("where");
+        let output = spawn(&dir, &full, "", envs);
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        path(&[], &[]),
+        format!(
+            "{}\nИсточник: по умолчанию\n",
+            dir.join("my-planner.txt").display()
+        )
+    );
+    // Путь показывается, даже если каталога еще нет
+    assert_eq!(
+        path(&[], &[("MY_PLANNER_PATH", "нет/env.txt")]),
+        format!(
+            "{}\nИсточник: переменная окружения MY_PLANNER_PATH\n",
+            dir.join("нет/env.txt").display()
+        )
+    );
+    assert_eq!(
+        path(&["--file", "flag.txt"], &[("MY_PLANNER_PATH", "env.txt")]),
+        format!(
+            "{}\nИсточник: флаг --file\n",
+            dir.join("flag.txt").display()
+        )
+    );
+}
+
+#[test]
+fn copy_adds_the_same_task_at_a_new_time() {
+    let dir = temp_dir("copy");
+    std::fs::write(
+        dir.join("planner.txt"),
+        "9:00\nотчёт\npriority: 1\ncategory: работа\nnotes: черновик\n\n",
+    )
+    .unwrap();
+
+    let output = run(&dir, &["copy", "1", "15:30"], "");
+    assert!(output.status.success());
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    let entries = list
+        .iter()
+        .map(|entry| {
+            (
+                entry.time().unwrap().to_string(),
+                entry.target(),
+                entry.category(),
+                entry.priority(),
+                entry.notes(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("9:00".to_owned(), "отчёт", "работа", 1, "черновик"),
+            ("15:30".to_owned(), "отчёт", "работа", 1, ""),
+        ]
+    );
+
+    for (args, error) in [
+        (["copy", "3", "16:00"], "Записи с таким номером нет."),
+        (["copy", "1", "25:00"], "Неверное время."),
+    ] {
+        let output = run(&dir, &args, "");
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains(error));
+    }
+    let list = Storage::new(&dir.join("planner.txt").to_string_lossy())
+        .read()
+        .unwrap();
+    assert_eq!(list.len(), 2);
+}
+
+#[test]
+fn english_runtime_messages_are_translated() {
+    let dir = temp_dir("english");
+    let en = |args: &[&str], input: &str| {
+        let mut full = vec!["--lang", "en"];
+        full.extend(args);
+        let output = run(&dir, &full, input);
+        String::from_utf8(output.stdout).unwrap() + &String::from_utf8(output.stderr).unwrap()
+    };
+
+    let added = en(&["add"], "9:00\nreport\n");
+    assert!(added.contains("Saved"), "{}", added);
+    assert!(added.contains("Entries added: 1"), "{}", added);
+    let list = en(&["list", "--all"], "");
+    for expected in [
+        "My schedule:",
+        "Time: 9:00",
+        "Task: [ ] report",
+        "Total tasks: 1 (done: 0)",
+    ] {
+        assert!(list.contains(expected), "{}", list);
+    }
+    assert!(en(&["move", "5", "10:00"], "").contains("Error: There is no entry with this number."));
+    assert!(en(&["undo"], "").contains("The last change was undone"));
+    assert!(en(&["undo"], "").contains("Error: Nothing to undo."));
+    assert!(en(&["bogus"], "").contains("Error: Unknown command: bogus"));
+}
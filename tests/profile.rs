@@ -0,0 +1,17 @@
+use my_planner::profile_path;
+use std::path::Path;
+
+/// Переменная окружения общая для всего процесса, поэтому тест
+/// живет в отдельном файле
+#[test]
+fn profile_path_follows_data_home() {
+    std::env::set_var("XDG_DATA_HOME", "/tmp/data");
+    assert_eq!(profile_path("work"), "/tmp/data/my_planner/work.txt");
+
+    std::env::set_var("XDG_DATA_HOME", "");
+    std::env::set_var("HOME", "/home/user");
+    assert_eq!(
+        Path::new(&profile_path("home")),
+        Path::new("/home/user/.local/share/my_planner/home.txt")
+    );
+}
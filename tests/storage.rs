@@ -0,0 +1,50 @@
+use my_planner::{Entry, EntryTrait, Storage};
+use std::fs::{create_dir_all, remove_file};
+
+/// Путь к файлу во временном каталоге, свой для каждого теста
+fn temp_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("my_planner_it_{}", std::process::id()));
+    create_dir_all(&dir).unwrap();
+    let path = dir.join(name).to_string_lossy().into_owned();
+    for suffix in ["", ".bak", ".lock", ".1"] {
+        let _ = remove_file(format!("{}{}", path, suffix));
+    }
+
+    path
+}
+
+/// Запись из JSON представления, как ее пишет формат `.json`
+fn entry(time: &str, target: &str) -> Box<dyn EntryTrait> {
+    let value = serde_json::json!({ "date": "2024-06-01", "time": time, "target": target });
+    Entry::try_from(&value).unwrap().into()
+}
+
+#[test]
+fn storage_saves_and_reads_through_public_api() {
+    let path = temp_path("public.txt");
+    let storage = Storage::new(&path).with_quiet(true);
+    storage.save(entry("14:00", "встреча")).unwrap();
+    storage.save(entry("8:00", "зарядка")).unwrap();
+
+    let targets = storage
+        .read()
+        .unwrap()
+        .iter()
+        .map(|entry| entry.target().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(targets, ["зарядка", "встреча"]);
+}
+
+#[test]
+fn storage_format_follows_extension() {
+    let path = temp_path("public.json");
+    let storage = Storage::new(&path).with_quiet(true);
+    storage
+        .save_all(vec![entry("9:00", "a"), entry("10:00", "b")])
+        .unwrap();
+
+    let value: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(value.as_array().map(Vec::len), Some(2));
+    assert_eq!(Storage::new(&path).read().unwrap().len(), 2);
+}